@@ -0,0 +1,66 @@
+//! Benchmarks of the compression function.  These use the unstable `test` crate, so run them on
+//! nightly with `cargo +nightly bench --features bench`.
+//!
+//! The crate only has the portable implementation of `F`, so there are no SIMD backends to compare
+//! here yet.
+#![feature(test)]
+
+extern crate test;
+
+use blake2b::blake2b::{decode_parameters, F};
+use blake2b::variant::{self, Variant};
+
+use test::Bencher;
+
+fn rounds_benchmark(rounds: usize, bencher: &mut Bencher) {
+    let input_bytes = hex::decode("0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001").unwrap();
+
+    let args = decode_parameters(&input_bytes).unwrap();
+
+    bencher.iter(|| F(rounds, &args.h, &args.m, &args.t, args.f).to_vec());
+}
+
+/// The generic compression function from `variant` with the standard constants, for comparison
+/// with `F`.
+fn variant_rounds_benchmark(rounds: usize, bencher: &mut Bencher) {
+    let input_bytes = hex::decode("0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001").unwrap();
+
+    let args = decode_parameters(&input_bytes).unwrap();
+    let constants = Variant::default();
+
+    bencher.iter(|| {
+        variant::compress(&constants, rounds, &args.h, &args.m, &args.t, args.f)
+            .unwrap()
+            .to_vec()
+    });
+}
+
+#[bench]
+fn bench_12_rounds(bencher: &mut Bencher) {
+    rounds_benchmark(12, bencher);
+}
+
+#[bench]
+fn bench_100_000_rounds(bencher: &mut Bencher) {
+    rounds_benchmark(100_000, bencher);
+}
+
+#[bench]
+fn bench_2_000_000_rounds(bencher: &mut Bencher) {
+    rounds_benchmark(2_000_000, bencher);
+}
+
+#[bench]
+fn bench_8_000_000_rounds(bencher: &mut Bencher) {
+    rounds_benchmark(8_000_000, bencher);
+}
+
+#[bench]
+fn bench_variant_12_rounds(bencher: &mut Bencher) {
+    variant_rounds_benchmark(12, bencher);
+}
+
+#[bench]
+fn bench_variant_100_000_rounds(bencher: &mut Bencher) {
+    variant_rounds_benchmark(100_000, bencher);
+}
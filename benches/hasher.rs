@@ -0,0 +1,94 @@
+//! Throughput benchmarks of the streaming hasher and the parallel hashing modes across message
+//! sizes.  These use the unstable `test` crate, so run them on nightly with
+//! `cargo +nightly bench --features bench`.
+//!
+//! Each iteration of the 1 GiB benchmarks takes seconds and the harness runs hundreds of them, so
+//! they are ignored by default.  Run them with `cargo +nightly bench --features bench -- --ignored`.
+#![feature(test)]
+
+extern crate test;
+
+use blake2b::hasher::{Blake2b, Params};
+use blake2b::parallel;
+
+use test::{black_box, Bencher};
+
+const KIB: usize = 1 << 10;
+const MIB: usize = 1 << 20;
+const GIB: usize = 1 << 30;
+
+/// The size of the chunks passed to `update`, so that the input is streamed rather than hashed
+/// in one call.
+const CHUNK_BYTES: usize = 64 * KIB;
+
+fn input(size: usize) -> Vec<u8> {
+    (0..size).map(|i| i as u8).collect()
+}
+
+fn hasher_benchmark(size: usize, bencher: &mut Bencher) {
+    let input = input(size);
+
+    bencher.bytes = size as u64;
+    bencher.iter(|| {
+        let mut hasher = Blake2b::new(64).unwrap();
+        for chunk in input.chunks(CHUNK_BYTES) {
+            hasher.update(black_box(chunk));
+        }
+        hasher.finalize()
+    });
+}
+
+fn blake2bp_benchmark(size: usize, bencher: &mut Bencher) {
+    let input = input(size);
+    let params = Params::default();
+
+    bencher.bytes = size as u64;
+    bencher.iter(|| parallel::blake2bp(&params, black_box(&input)).unwrap());
+}
+
+#[bench]
+fn bench_hasher_64_b(bencher: &mut Bencher) {
+    hasher_benchmark(64, bencher);
+}
+
+#[bench]
+fn bench_hasher_1_kib(bencher: &mut Bencher) {
+    hasher_benchmark(KIB, bencher);
+}
+
+#[bench]
+fn bench_hasher_64_kib(bencher: &mut Bencher) {
+    hasher_benchmark(64 * KIB, bencher);
+}
+
+#[bench]
+fn bench_hasher_1_mib(bencher: &mut Bencher) {
+    hasher_benchmark(MIB, bencher);
+}
+
+#[bench]
+fn bench_hasher_16_mib(bencher: &mut Bencher) {
+    hasher_benchmark(16 * MIB, bencher);
+}
+
+#[bench]
+#[ignore]
+fn bench_hasher_1_gib(bencher: &mut Bencher) {
+    hasher_benchmark(GIB, bencher);
+}
+
+#[bench]
+fn bench_blake2bp_64_kib(bencher: &mut Bencher) {
+    blake2bp_benchmark(64 * KIB, bencher);
+}
+
+#[bench]
+fn bench_blake2bp_16_mib(bencher: &mut Bencher) {
+    blake2bp_benchmark(16 * MIB, bencher);
+}
+
+#[bench]
+#[ignore]
+fn bench_blake2bp_1_gib(bencher: &mut Bencher) {
+    blake2bp_benchmark(GIB, bencher);
+}
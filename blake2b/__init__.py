@@ -1,21 +1,204 @@
 """
 Functions for calculating blake2b hashes.
 """
+import hashlib
+from typing import (
+    Any,
+    Callable,
+    Dict,
+)
+
 from .blake2b import (  # noqa: F401
+    Blake2b,
+    CompressArgs,
+    CompressTrace,
+    G,
+    GenericHashState,
+    HMAC,
+    LimitExceeded,
+    compare_digest,
     compress,
+    compress_trace,
+    crypto_generichash,
+    crypto_generichash_blake2b_salt_personal,
+    crypto_generichash_final,
+    crypto_generichash_init,
+    crypto_generichash_update,
     decode_and_compress,
     decode_parameters,
+    get_async_workers,
+    get_limits,
+    hash_file,
+    hkdf,
+    hkdf_expand,
+    hkdf_extract,
+    hmac_blake2b,
+    kdf,
+    kdf_expand,
+    kdf_extract,
+    pbkdf2_hmac_blake2b,
+    permute,
+    round,
+    set_async_workers,
+    set_limits,
+    verify,
+)
+from ._asyncio import (  # noqa: F401
+    compress_async,
+    crypto_generichash_update_async,
+    decode_and_compress_async,
 )
 
+# ``round`` is left out so that ``from blake2b import *`` doesn't shadow the
+# builtin.  Use it as ``blake2b.round``.
 __all__ = [
+    'Blake2b',
+    'CompressArgs',
+    'CompressTrace',
+    'G',
+    'GenericHashState',
+    'HMAC',
+    'LimitExceeded',
+    'compare_digest',
     'compress',
+    'compress_async',
+    'compress_trace',
+    'crypto_generichash',
+    'crypto_generichash_blake2b_salt_personal',
+    'crypto_generichash_final',
+    'crypto_generichash_init',
+    'crypto_generichash_update',
+    'crypto_generichash_update_async',
     'decode_and_compress',
+    'decode_and_compress_async',
     'decode_parameters',
+    'get_async_workers',
+    'get_limits',
+    'hash_file',
+    'hkdf',
+    'hkdf_expand',
+    'hkdf_extract',
+    'hmac_blake2b',
+    'kdf',
+    'kdf_expand',
+    'kdf_extract',
+    'new',
+    'pbkdf2_hmac_blake2b',
+    'permute',
+    'register',
+    'set_async_workers',
+    'set_limits',
     'test',
+    'unregister',
+    'verify',
 ]
 
+# The names passed to ``register``, keyed by their lower case form
+_constructors: Dict[str, Callable[..., Any]] = {}
+# The names ``register`` added to ``hashlib.algorithms_available``, which
+# ``unregister`` takes out again
+_added_names: Dict[str, str] = {}
+# Whether ``_new`` has been installed as ``hashlib.new``, and the function it
+# falls back to for other names
+_installed = False
+_hashlib_new = hashlib.new
+
+
+def _new(name: str, data: bytes = b'', **kwargs: Any) -> Any:
+    constructor = _constructors.get(name.lower())
+    if constructor is None:
+        return _hashlib_new(name, data, **kwargs)
+    return constructor(data, **kwargs)
+
+
+def new(name: str, data: bytes = b'', **kwargs: Any) -> Any:
+    """
+    Like ``hashlib.new``, but returns a ``Blake2b`` hash object for
+    ``'blake2b'``, ``'blake2b-rs'`` and any name passed to ``register``.  Other
+    names are passed on to ``hashlib.new``.  Unlike ``register`` this has no
+    global side effects, so prefer it when the code choosing hashes by name is
+    your own.
+
+    Parameters
+    ----------
+    name : str
+        The name of the hash, matched case insensitively.
+    data : bytes
+        Initial input to pass to ``update``.
+    **kwargs
+        Passed on to the hash object's constructor, such as `digest_size`.
+    """
+    if name.lower() in ('blake2b', 'blake2b-rs'):
+        return Blake2b(data, **kwargs)
+    return _new(name, data, **kwargs)
+
+
+def register(name: str = 'blake2b-rs') -> None:
+    """
+    Make ``hashlib.new(name)`` return a ``Blake2b`` hash object, so that code
+    choosing hashes by name picks up this implementation without changes.
+    Keyword arguments such as ``digest_size`` are passed on, and `name` is
+    added to ``hashlib.algorithms_available``.
+
+    This replaces ``hashlib.new`` for the whole process, which every other
+    module sees as well.  Other names are still passed on to the function it
+    replaced.  Registering a name again does nothing, and ``unregister`` undoes
+    it.  Use ``new`` instead to avoid the global side effect.
+
+    The registered name is also accepted as `digestmod` by ``hmac.new``,
+    except on Python 3.10 builds against OpenSSL 3.  Passing ``'blake2b'``
+    replaces the standard library BLAKE2b in ``hashlib.new``, but on Python
+    3.9 and later ``hmac.new`` hands digest names that OpenSSL knows straight
+    to OpenSSL.  Pass ``Blake2b`` itself as `digestmod` to be sure of using
+    this implementation.
+
+    Parameters
+    ----------
+    name : str
+        The name to register, matched case insensitively.
+    """
+    global _hashlib_new, _installed
+
+    key = name.lower()
+    if key in _constructors:
+        return
+
+    _constructors[key] = Blake2b
+    if name not in hashlib.algorithms_available:
+        hashlib.algorithms_available.add(name)
+        _added_names[key] = name
+    if not _installed:
+        _hashlib_new = hashlib.new
+        hashlib.new = _new
+        _installed = True
+
+
+def unregister(name: str = 'blake2b-rs') -> None:
+    """
+    Undo ``register(name)``.  Once no names are left registered, the original
+    ``hashlib.new`` is put back, unless it has since been replaced again by
+    someone else.  Unregistering a name that isn't registered does nothing.
+
+    Parameters
+    ----------
+    name : str
+        The name to unregister, matched case insensitively.
+    """
+    global _installed
+
+    key = name.lower()
+    if _constructors.pop(key, None) is None:
+        return
+
+    added = _added_names.pop(key, None)
+    if added is not None:
+        hashlib.algorithms_available.discard(added)
+    if not _constructors and hashlib.new is _new:
+        hashlib.new = _hashlib_new
+        _installed = False
+
 
-def test():
+def test() -> None:
     """
     Run a basic test suite to verify correct installation and hash output.
     """
@@ -0,0 +1,128 @@
+"""
+Coroutine variants of the slow functions, which run on a pool of worker
+threads managed by the extension module so they don't block the event loop.
+"""
+import asyncio
+from typing import Any, Callable, Optional, Sequence, Union
+
+from .blake2b import (
+    GenericHashState,
+    submit_compress,
+    submit_decode_and_compress,
+    submit_generichash_update,
+)
+
+
+def _resolve(future: 'asyncio.Future[Any]',
+             result: Any,
+             error: Optional[BaseException]) -> None:
+    if future.cancelled():
+        return
+    if error is not None:
+        future.set_exception(error)
+    else:
+        future.set_result(result)
+
+
+def _run_in_pool(submit: Callable[..., None],
+                 *args: Any,
+                 **kwargs: Any) -> 'asyncio.Future[Any]':
+    loop = asyncio.get_running_loop()
+    future = loop.create_future()
+
+    def callback(result: Any, error: Optional[BaseException]) -> None:
+        loop.call_soon_threadsafe(_resolve, future, result, error)
+
+    submit(*args, callback, **kwargs)
+    return future
+
+
+async def decode_and_compress_async(
+        input: Union[bytes, Sequence[int]],
+        max_rounds: Optional[int] = None,
+        time_budget: Optional[float] = None) -> bytes:
+    """
+    Calculates a blake2b hash for the tightly encoded input given in the byte
+    sequence `input` on a worker thread.  See ``decode_and_compress``.
+
+    Parameters
+    ----------
+    input : bytes, List[int]
+        A vector of 213 bytes representing the tightly encoded input.
+    max_rounds : int, optional
+        The largest number of rounds allowed.  Defaults to the limit set with
+        ``set_limits``.
+    time_budget : float, optional
+        The longest the call is allowed to take in seconds, counted from when
+        a worker thread starts on it.  Defaults to the limit set with
+        ``set_limits``.
+
+    Returns
+    -------
+    out : bytes
+        A vector of 64 bytes representing the blake2b hash of the input data.
+
+    Raises
+    ------
+    LimitExceeded
+        If the number of rounds is over either limit, or the call runs out of
+        time.  No hash is returned.
+    """
+    return await _run_in_pool(
+        submit_decode_and_compress,
+        input,
+        max_rounds=max_rounds,
+        time_budget=time_budget,
+    )
+
+
+async def compress_async(rounds: int,
+                         starting_state: Sequence[int],
+                         block: Sequence[int],
+                         offset_counters: Sequence[int],
+                         final_block_flag: bool,
+                         max_rounds: Optional[int] = None,
+                         time_budget: Optional[float] = None) -> bytes:
+    """
+    Calculates a blake2b hash for the given message block on a worker thread.
+    See ``compress``, which takes the same arguments.  The time budget is
+    counted from when a worker thread starts on the call.
+
+    Returns
+    -------
+    out : bytes
+        A vector of 64 bytes representing the blake2b hash of the input data.
+
+    Raises
+    ------
+    LimitExceeded
+        If `rounds` is over either limit, or the call runs out of time.  No
+        hash is returned.
+    """
+    return await _run_in_pool(
+        submit_compress,
+        rounds,
+        starting_state,
+        block,
+        offset_counters,
+        final_block_flag,
+        max_rounds=max_rounds,
+        time_budget=time_budget,
+    )
+
+
+async def crypto_generichash_update_async(state: GenericHashState,
+                                          message: bytes) -> None:
+    """
+    Absorbs `message` into the multi-part hashing state `state` on a worker
+    thread.  See ``crypto_generichash_update``.  The state can't be used
+    again until the update has completed.
+
+    Parameters
+    ----------
+    state : GenericHashState
+        A hashing state created by ``crypto_generichash_init``.
+    message : bytes
+        The next part of the message to be hashed.
+    """
+    await _run_in_pool(submit_generichash_update, state, message)
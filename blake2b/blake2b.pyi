@@ -0,0 +1,306 @@
+"""
+Type stubs for the compiled extension module.
+"""
+import os
+from typing import (
+    Any,
+    Callable,
+    Iterator,
+    List,
+    Optional,
+    Sequence,
+    Tuple,
+    Union,
+)
+
+_Input = Union[bytes, Sequence[int]]
+_CompressTuple = Tuple[int, List[int], List[int], List[int], bool]
+_TraceItem = Tuple[int, Optional[int], List[int], List[int]]
+_Callback = Callable[[Any, Optional[BaseException]], None]
+
+
+class LimitExceeded(ValueError):
+    ...
+
+
+class CompressArgs:
+    @staticmethod
+    def from_bytes(input: bytes) -> CompressArgs: ...
+    @staticmethod
+    def block(
+        block: bytes,
+        rounds: int = 12,
+        h: Optional[Sequence[int]] = None,
+        t: Optional[Sequence[int]] = None,
+        f: bool = False,
+    ) -> CompressArgs: ...
+    @staticmethod
+    def from_tuple(
+        args: Tuple[int, Sequence[int], Sequence[int], Sequence[int], bool],
+    ) -> CompressArgs: ...
+    def to_tuple(self) -> _CompressTuple: ...
+    def to_bytes(self) -> bytes: ...
+    def compress(
+        self,
+        max_rounds: Optional[int] = None,
+        time_budget: Optional[float] = None,
+    ) -> bytes: ...
+    @property
+    def rounds(self) -> int: ...
+    @property
+    def h(self) -> List[int]: ...
+    @property
+    def m(self) -> List[int]: ...
+    @property
+    def t(self) -> List[int]: ...
+    @property
+    def f(self) -> bool: ...
+    def __eq__(self, other: object) -> bool: ...
+
+
+class CompressTrace:
+    def output(self) -> bytes: ...
+    def __iter__(self) -> Iterator[_TraceItem]: ...
+    def __next__(self) -> _TraceItem: ...
+
+
+class GenericHashState:
+    def copy(self) -> GenericHashState: ...
+    def midstate(self) -> Tuple[List[int], List[int], bytes]: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
+    def __reduce__(self) -> Tuple[Any, Tuple[()], bytes]: ...
+
+
+class Blake2b:
+    SALT_SIZE: int
+    PERSON_SIZE: int
+    MAX_KEY_SIZE: int
+    MAX_DIGEST_SIZE: int
+
+    def __init__(
+        self,
+        data: Optional[bytes] = None,
+        *,
+        digest_size: int = 64,
+        key: Optional[bytes] = None,
+        salt: Optional[bytes] = None,
+        person: Optional[bytes] = None,
+        fanout: int = 1,
+        depth: int = 1,
+        leaf_size: int = 0,
+        node_offset: int = 0,
+        node_depth: int = 0,
+        inner_size: int = 0,
+        last_node: bool = False,
+        usedforsecurity: bool = True,
+    ) -> None: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def digest_size(self) -> int: ...
+    @property
+    def block_size(self) -> int: ...
+    def update(self, data: bytes) -> None: ...
+    def digest(self) -> bytes: ...
+    def hexdigest(self) -> str: ...
+    def copy(self) -> Blake2b: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
+    def __reduce__(self) -> Tuple[Any, Tuple[()], bytes]: ...
+
+
+class HMAC:
+    def __init__(self, key: bytes, msg: Optional[bytes] = None) -> None: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def digest_size(self) -> int: ...
+    @property
+    def block_size(self) -> int: ...
+    def update(self, msg: bytes) -> None: ...
+    def digest(self) -> bytes: ...
+    def hexdigest(self) -> str: ...
+    def copy(self) -> HMAC: ...
+    def verify(self, tag: bytes) -> bool: ...
+
+
+def decode_parameters(input: _Input) -> _CompressTuple: ...
+
+
+def compress(
+    rounds: int,
+    starting_state: Sequence[int],
+    block: Sequence[int],
+    offset_counters: Sequence[int],
+    final_block_flag: bool,
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> bytes: ...
+
+
+def decode_and_compress(
+    input: _Input,
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> bytes: ...
+
+
+def set_limits(
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> None: ...
+
+
+def get_limits() -> Tuple[Optional[int], Optional[float]]: ...
+
+
+def compress_trace(
+    rounds: int,
+    starting_state: Sequence[int],
+    block: Sequence[int],
+    offset_counters: Sequence[int],
+    final_block_flag: bool,
+    per_round: bool = False,
+) -> CompressTrace: ...
+
+
+def G(
+    v: Sequence[int],
+    a: int,
+    b: int,
+    c: int,
+    d: int,
+    x: int,
+    y: int,
+) -> List[int]: ...
+
+
+def round(v: Sequence[int], m: Sequence[int], r: int) -> List[int]: ...
+
+
+def permute(
+    v: Sequence[int],
+    rounds: int = 12,
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> List[int]: ...
+
+
+def crypto_generichash(
+    message: bytes,
+    outlen: int = 32,
+    key: Optional[bytes] = None,
+) -> bytes: ...
+
+
+def crypto_generichash_blake2b_salt_personal(
+    message: bytes,
+    outlen: int = 32,
+    key: Optional[bytes] = None,
+    salt: Optional[bytes] = None,
+    personal: Optional[bytes] = None,
+) -> bytes: ...
+
+
+def crypto_generichash_init(
+    key: Optional[bytes] = None,
+    outlen: int = 32,
+) -> GenericHashState: ...
+
+
+def crypto_generichash_update(state: GenericHashState, message: bytes) -> None: ...
+
+
+def crypto_generichash_final(state: GenericHashState) -> bytes: ...
+
+
+def hash_file(
+    path: Union[str, os.PathLike],
+    mode: str = 'blake2b',
+    digest_size: int = 64,
+    key: Optional[bytes] = None,
+) -> bytes: ...
+
+
+def set_async_workers(workers: int) -> None: ...
+
+
+def get_async_workers() -> int: ...
+
+
+def submit_decode_and_compress(
+    input: _Input,
+    callback: _Callback,
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> None: ...
+
+
+def submit_compress(
+    rounds: int,
+    starting_state: Sequence[int],
+    block: Sequence[int],
+    offset_counters: Sequence[int],
+    final_block_flag: bool,
+    callback: _Callback,
+    max_rounds: Optional[int] = None,
+    time_budget: Optional[float] = None,
+) -> None: ...
+
+
+def submit_generichash_update(
+    state: GenericHashState,
+    message: bytes,
+    callback: _Callback,
+) -> None: ...
+
+
+def kdf(
+    ikm: bytes,
+    salt: Optional[bytes] = None,
+    info: Optional[bytes] = None,
+    length: int = 64,
+) -> bytes: ...
+
+
+def kdf_extract(salt: bytes, ikm: bytes) -> bytes: ...
+
+
+def kdf_expand(prk: bytes, info: bytes, length: int) -> bytes: ...
+
+
+def hkdf(
+    ikm: bytes,
+    salt: Optional[bytes] = None,
+    info: Optional[bytes] = None,
+    length: int = 64,
+) -> bytes: ...
+
+
+def pbkdf2_hmac_blake2b(
+    password: bytes,
+    salt: bytes,
+    iterations: int,
+    dklen: Optional[int] = None,
+) -> bytes: ...
+
+
+def hkdf_extract(salt: bytes, ikm: bytes) -> bytes: ...
+
+
+def hkdf_expand(prk: bytes, info: bytes, length: int) -> bytes: ...
+
+
+def hmac_blake2b(key: bytes, msg: bytes) -> bytes: ...
+
+
+def compare_digest(a: bytes, b: bytes) -> bool: ...
+
+
+def verify(
+    expected_tag: bytes,
+    key: bytes,
+    message: bytes,
+    digest_size: int = 64,
+) -> bool: ...
@@ -0,0 +1,34 @@
+#![no_main]
+
+//! The input is three bytes giving the lengths of the starting state, block and offset counter
+//! vectors, followed by the 213 byte precompile encoding.  The vectors are filled from the words
+//! of the encoding, so that the seeds built from the EIP-152 vectors use the correct lengths.
+
+use libfuzzer_sys::fuzz_target;
+
+use blake2b::blake2b::checked_compress;
+use blake2b_py_fuzz::{words, MAX_ROUNDS};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 8 {
+        return;
+    }
+    let (lengths, rest) = data.split_at(3);
+    let (rounds, rest) = rest.split_at(4);
+    let rounds = u32::from_be_bytes([rounds[0], rounds[1], rounds[2], rounds[3]]) as usize;
+    if rounds > MAX_ROUNDS {
+        return;
+    }
+
+    let mut words = words(rest).into_iter();
+    let h: Vec<u64> = words.by_ref().take(lengths[0] as usize % 24).collect();
+    let m: Vec<u64> = words.by_ref().take(lengths[1] as usize % 24).collect();
+    let t: Vec<u64> = words.by_ref().take(lengths[2] as usize % 24).collect();
+    let f = data[data.len() - 1] & 1 == 1;
+
+    let result = checked_compress(rounds, &h, &m, &t, f);
+    assert_eq!(
+        result.is_ok(),
+        h.len() == 8 && m.len() == 16 && t.len() == 2
+    );
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use blake2b::blake2b::{decode_parameters, CompressArgs, F};
+use blake2b::capi::{blake2b_decode_and_compress, BLAKE2B_OK};
+use blake2b_py_fuzz::MAX_ROUNDS;
+
+fuzz_target!(|data: &[u8]| {
+    let CompressArgs { rounds, h, m, t, f } = match decode_parameters(data) {
+        Ok(args) => args,
+        Err(_) => return,
+    };
+    if rounds > MAX_ROUNDS {
+        return;
+    }
+
+    let expected = F(rounds, &h, &m, &t, f);
+
+    // The C ABI wraps the same decoding and compression steps
+    let mut out = [0u8; 64];
+    let code = unsafe { blake2b_decode_and_compress(data.as_ptr(), data.len(), out.as_mut_ptr()) };
+    assert_eq!(code, BLAKE2B_OK);
+    assert_eq!(out, expected);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use blake2b::blake2b::decode_parameters;
+
+fuzz_target!(|data: &[u8]| {
+    match decode_parameters(data) {
+        Ok(_) => assert!(data.len() == 213 && data[212] <= 1),
+        Err(_) => assert!(data.len() != 213 || data[212] > 1),
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+//! Compare F against the reference implementation in `tests/reference/mod.rs` over decoded
+//! precompile input.
+
+use libfuzzer_sys::fuzz_target;
+
+use blake2b::blake2b::{decode_parameters, CompressArgs, F};
+use blake2b_py_fuzz::{reference, MAX_ROUNDS};
+
+fuzz_target!(|data: &[u8]| {
+    let CompressArgs { rounds, h, m, t, f } = match decode_parameters(data) {
+        Ok(args) => args,
+        Err(_) => return,
+    };
+    // Keep exploring large round counts without spending the whole time budget on them
+    let rounds = rounds % MAX_ROUNDS;
+
+    assert_eq!(
+        F(rounds, &h, &m, &t, f),
+        reference::F(rounds, &h, &m, &t, f)
+    );
+});
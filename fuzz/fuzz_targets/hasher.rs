@@ -0,0 +1,33 @@
+#![no_main]
+
+//! The input is a digest length byte, a key length byte, a chunk size byte, the key and then the
+//! message.  The message is absorbed in chunks and the result compared with one-shot hashing.
+
+use libfuzzer_sys::fuzz_target;
+
+use blake2b::hasher::{self, Blake2b, Params, KEYBYTES, OUTBYTES};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 3 {
+        return;
+    }
+    let digest_length = data[0] as usize % OUTBYTES + 1;
+    let key_length = (data[1] as usize % (KEYBYTES + 1)).min(data.len() - 3);
+    let chunk_size = data[2] as usize + 1;
+    let (key, message) = data[3..].split_at(key_length);
+
+    let params = Params {
+        digest_length,
+        key: key.to_vec(),
+        ..Params::default()
+    };
+    let expected = hasher::hash(&params, message).unwrap();
+
+    let mut state = Blake2b::with_params(&params).unwrap();
+    for chunk in message.chunks(chunk_size) {
+        state.update(chunk);
+    }
+
+    assert_eq!(state.finalize(), expected);
+    assert_eq!(expected.len(), digest_length);
+});
@@ -0,0 +1,20 @@
+//! Helpers shared by the fuzz targets.
+
+#[path = "../../tests/reference/mod.rs"]
+pub mod reference;
+
+/// The largest number of rounds the fuzz targets run the compression function for.  Inputs asking
+/// for more are still decoded, but compressing them would only make the fuzzer time out.
+pub const MAX_ROUNDS: usize = 1 << 12;
+
+/// Read little endian 64-bit words from `input`, ignoring any trailing partial word.
+pub fn words(input: &[u8]) -> Vec<u64> {
+    input
+        .chunks_exact(8)
+        .map(|word| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(word);
+            u64::from_le_bytes(bytes)
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+#ifndef BLAKE2B_H
+#define BLAKE2B_H
+
+/* This file is generated by `make header` from src/capi.rs.  Don't edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The call succeeded.
+#define BLAKE2B_OK 0
+
+// A required pointer argument was NULL.
+#define BLAKE2B_ERR_NULL_POINTER (-1)
+
+// The precompile input was not exactly `BLAKE2B_F_INPUT_BYTES` bytes long.
+#define BLAKE2B_ERR_INPUT_LENGTH (-2)
+
+// The final block indicator flag of the precompile input was neither 0 nor 1.
+#define BLAKE2B_ERR_FINAL_BLOCK_FLAG (-3)
+
+// An output buffer length didn't match the digest length of the hasher.
+#define BLAKE2B_ERR_OUTPUT_LENGTH (-4)
+
+// Number of bytes in the tightly packed input to the blake2 F precompile.
+#define BLAKE2B_F_INPUT_BYTES 213
+
+// Number of bytes in the output of the blake2 F compression function.
+#define BLAKE2B_F_OUTPUT_BYTES 64
+
+// An opaque streaming blake2b hashing state.  Create one with `blake2b_hasher_new` and release
+// it with either `blake2b_hasher_finalize` or `blake2b_hasher_free`.
+typedef struct blake2b_hasher blake2b_hasher;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Run `rounds` rounds of the blake2b compression function F over the state vector `h`, message
+// block vector `m` and offset counter vector `t`, with final block indicator flag `f`, and write
+// the 64 byte result to `out`.
+//
+// # Safety
+//
+// `h`, `m`, `t` and `out` must point to 8, 16, 2 and 64 valid elements respectively.
+int32_t blake2b_f(uint32_t rounds,
+                  const uint64_t *h,
+                  const uint64_t *m,
+                  const uint64_t *t,
+                  bool f,
+                  uint8_t *out);
+
+// Decode `input_len` bytes of tightly packed blake2 F precompile input at `input`, run the
+// compression function over them and write the 64 byte result to `out`.
+//
+// See here: https://eips.ethereum.org/EIPS/eip-152#specification
+//
+// # Safety
+//
+// `input` must point to `input_len` valid bytes and `out` to 64 valid bytes.
+int32_t blake2b_decode_and_compress(const uint8_t *input, size_t input_len, uint8_t *out);
+
+// Create a streaming hasher producing `digest_length` byte digests, keyed with the `key_len`
+// bytes at `key`.  Pass a NULL `key` and a `key_len` of 0 for unkeyed hashing.  Returns NULL if
+// `digest_length` isn't between 1 and 64 or `key_len` is greater than 64.
+//
+// # Safety
+//
+// `key` must point to `key_len` valid bytes.
+struct blake2b_hasher *blake2b_hasher_new(size_t digest_length, const uint8_t *key, size_t key_len);
+
+// Absorb the `input_len` bytes at `input` into `hasher`.
+//
+// # Safety
+//
+// `hasher` must have been returned by `blake2b_hasher_new` and not yet released, and `input`
+// must point to `input_len` valid bytes.
+int32_t blake2b_hasher_update(struct blake2b_hasher *hasher,
+                              const uint8_t *input,
+                              size_t input_len);
+
+// Write the digest of all input absorbed by `hasher` to the `out_len` bytes at `out`, then
+// release `hasher`.  `out_len` must equal the digest length `hasher` was created with.  `hasher`
+// is released even if an error is returned.
+//
+// # Safety
+//
+// `hasher` must have been returned by `blake2b_hasher_new` and not yet released, and `out` must
+// point to `out_len` valid bytes.
+int32_t blake2b_hasher_finalize(struct blake2b_hasher *hasher, uint8_t *out, size_t out_len);
+
+// Release `hasher` without computing its digest.  Passing NULL does nothing.
+//
+// # Safety
+//
+// `hasher` must be NULL or have been returned by `blake2b_hasher_new` and not yet released.
+void blake2b_hasher_free(struct blake2b_hasher *hasher);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BLAKE2B_H */
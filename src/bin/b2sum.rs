@@ -0,0 +1,819 @@
+//! A `b2sum` work-alike built on this crate's blake2b hasher.
+//!
+//! The output format, the `--check` mode and the exit statuses follow GNU coreutils' `b2sum` so
+//! that checksum files produced by either program can be verified by the other.
+//!
+//! Build it without the Python bindings:
+//!
+//! ```text
+//! cargo build --release --no-default-features --bin b2sum
+//! ```
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::process;
+
+use blake2b::hasher::{Blake2b, OUTBYTES};
+
+const PROGRAM: &str = "b2sum";
+const ALGORITHM: &str = "BLAKE2b";
+const READ_SIZE: usize = 64 * 1024;
+
+const USAGE: &str = "\
+Usage: b2sum [OPTION]... [FILE]...
+Print or check BLAKE2b (512-bit) checksums.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -b, --binary         read in binary mode
+  -c, --check          read BLAKE2b sums from the FILEs and check them
+  -l, --length         digest length in bits; must not exceed the maximum for
+                         the blake2 algorithm and must be a multiple of 8
+      --tag            create a BSD-style checksum
+  -t, --text           read in text mode (default)
+  -z, --zero           end each output line with NUL, not newline,
+                         and disable file name escaping
+
+The following five options are useful only when verifying checksums:
+      --ignore-missing  don't fail or report status for missing files
+      --quiet          don't print OK for each successfully verified file
+      --status         don't output anything, status code shows success
+      --strict         exit non-zero for improperly formatted checksum lines
+  -w, --warn           warn about improperly formatted checksum lines
+
+      --help     display this help and exit
+      --version  output version information and exit
+";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    binary: bool,
+    check: bool,
+    digest_length: usize,
+    tag: bool,
+    zero: bool,
+    ignore_missing: bool,
+    quiet: bool,
+    status: bool,
+    strict: bool,
+    warn: bool,
+    files: Vec<OsString>,
+}
+
+/// The reason parsing the command line stopped short of producing `Options`.
+#[derive(Debug, PartialEq)]
+enum Exit {
+    Help,
+    Version,
+    /// An error to report along with a hint to run `--help`.
+    Usage(String),
+    /// An error to report on its own.
+    Fatal(Vec<String>),
+}
+
+fn parse_length(value: &str) -> Result<usize, Exit> {
+    let invalid = format!("invalid length: '{}'", value);
+    let bits: usize = value
+        .parse()
+        .map_err(|_| Exit::Fatal(vec![invalid.clone()]))?;
+
+    if bits > OUTBYTES * 8 {
+        return Err(Exit::Fatal(vec![
+            invalid,
+            format!(
+                "maximum digest length for '{}' is {} bits",
+                ALGORITHM,
+                OUTBYTES * 8,
+            ),
+        ]));
+    }
+    if !bits.is_multiple_of(8) {
+        return Err(Exit::Fatal(vec![
+            invalid,
+            "length is not a multiple of 8".to_string(),
+        ]));
+    }
+
+    Ok(if bits == 0 { OUTBYTES } else { bits / 8 })
+}
+
+/// Parse the command line.  File names are kept exactly as given, while options, which are all
+/// ASCII, are matched against a lossy UTF-8 conversion of each argument.
+fn parse_args<I: IntoIterator<Item = OsString>>(args: I) -> Result<Options, Exit> {
+    let mut options = Options {
+        digest_length: OUTBYTES,
+        ..Options::default()
+    };
+    let mut args = args.into_iter();
+    let lossy = |arg: OsString| arg.to_string_lossy().into_owned();
+
+    while let Some(os_arg) = args.next() {
+        let arg = os_arg.to_string_lossy();
+        if arg == "--" {
+            options.files.extend(args.by_ref());
+            break;
+        } else if arg.starts_with("--") {
+            let (name, value) = match arg.find('=') {
+                Some(i) => (&arg[..i], Some(arg[i + 1..].to_string())),
+                None => (&arg[..], None),
+            };
+            match name {
+                "--binary" => options.binary = true,
+                "--check" => options.check = true,
+                "--tag" => options.tag = true,
+                "--text" => options.binary = false,
+                "--zero" => options.zero = true,
+                "--ignore-missing" => options.ignore_missing = true,
+                "--quiet" => options.quiet = true,
+                "--status" => options.status = true,
+                "--strict" => options.strict = true,
+                "--warn" => options.warn = true,
+                "--help" => return Err(Exit::Help),
+                "--version" => return Err(Exit::Version),
+                "--length" => {
+                    let value = match value.or_else(|| args.next().map(lossy)) {
+                        Some(value) => value,
+                        None => {
+                            return Err(Exit::Usage(
+                                "option '--length' requires an argument".to_string(),
+                            ))
+                        }
+                    };
+                    options.digest_length = parse_length(&value)?;
+                    continue;
+                }
+                _ => return Err(Exit::Usage(format!("unrecognized option '{}'", arg))),
+            }
+            if value.is_some() {
+                return Err(Exit::Usage(format!(
+                    "option '{}' doesn't allow an argument",
+                    name
+                )));
+            }
+        } else if arg.starts_with('-') && arg.len() > 1 {
+            for (i, flag) in arg.char_indices().skip(1) {
+                match flag {
+                    'b' => options.binary = true,
+                    'c' => options.check = true,
+                    't' => options.binary = false,
+                    'z' => options.zero = true,
+                    'w' => options.warn = true,
+                    'l' => {
+                        let rest = &arg[i + 1..];
+                        let value = if rest.is_empty() {
+                            match args.next().map(lossy) {
+                                Some(value) => value,
+                                None => {
+                                    return Err(Exit::Usage(
+                                        "option requires an argument -- 'l'".to_string(),
+                                    ))
+                                }
+                            }
+                        } else {
+                            rest.to_string()
+                        };
+                        options.digest_length = parse_length(&value)?;
+                        break;
+                    }
+                    _ => return Err(Exit::Usage(format!("invalid option -- '{}'", flag))),
+                }
+            }
+        } else {
+            options.files.push(os_arg);
+        }
+    }
+
+    if options.check && options.tag {
+        return Err(Exit::Usage(
+            "the --tag option is meaningless when verifying checksums".to_string(),
+        ));
+    }
+    if options.check && options.zero {
+        return Err(Exit::Usage(
+            "the --zero option is not supported when verifying checksums".to_string(),
+        ));
+    }
+    if !options.check {
+        for (flag, set) in &[
+            ("--ignore-missing", options.ignore_missing),
+            ("--quiet", options.quiet),
+            ("--status", options.status),
+            ("--strict", options.strict),
+            ("--warn", options.warn),
+        ] {
+            if *set {
+                return Err(Exit::Usage(format!(
+                    "the {} option is meaningful only when verifying checksums",
+                    flag
+                )));
+            }
+        }
+    }
+    if options.files.is_empty() {
+        options.files.push(OsString::from("-"));
+    }
+
+    Ok(options)
+}
+
+/// Format an I/O error the way the C library's `strerror` would, without Rust's trailing
+/// "(os error N)".
+fn describe_error(err: &io::Error) -> String {
+    let message = err.to_string();
+    match message.find(" (os error ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
+}
+
+/// The bytes of the file name `name`, which are printed and escaped as they are, like coreutils
+/// does.  Names that aren't valid UTF-8 are only kept intact on unix.
+#[cfg(unix)]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    name.as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    name.to_string_lossy().into_owned().into_bytes()
+}
+
+/// The path of the file whose name has the bytes `name`.
+#[cfg(unix)]
+fn name_path(name: &[u8]) -> PathBuf {
+    PathBuf::from(OsStr::from_bytes(name))
+}
+
+#[cfg(not(unix))]
+fn name_path(name: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(name).into_owned())
+}
+
+fn open(name: &[u8]) -> io::Result<Box<dyn Read>> {
+    if name == b"-" {
+        Ok(Box::new(io::stdin()))
+    } else {
+        Ok(Box::new(File::open(name_path(name))?))
+    }
+}
+
+/// Hash everything readable from `input` into a digest of `digest_length` bytes.
+fn digest_reader<R: Read>(mut input: R, digest_length: usize) -> io::Result<Vec<u8>> {
+    let mut state = Blake2b::new(digest_length).expect("digest length is validated");
+    let mut buf = vec![0u8; READ_SIZE];
+
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(state.finalize()),
+            Ok(n) => state.update(&buf[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn digest_file(name: &[u8], digest_length: usize) -> io::Result<Vec<u8>> {
+    digest_reader(open(name)?, digest_length)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.iter().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Find the last occurrence of `needle` in `haystack`.
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+/// Escape backslashes and line breaks in `name`, returning whether any escaping was needed.
+fn escape_name(name: &[u8]) -> (Vec<u8>, bool) {
+    let mut escaped = Vec::with_capacity(name.len());
+    for &b in name {
+        match b {
+            b'\\' => escaped.extend_from_slice(b"\\\\"),
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            b'\r' => escaped.extend_from_slice(b"\\r"),
+            b => escaped.push(b),
+        }
+    }
+    let needs_escape = escaped.len() != name.len();
+
+    (escaped, needs_escape)
+}
+
+/// Undo `escape_name`, failing on any escape sequence it would not have produced.
+fn unescape_name(name: &[u8]) -> Option<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(name.len());
+    let mut bytes = name.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'\\' {
+            match bytes.next()? {
+                b'\\' => unescaped.push(b'\\'),
+                b'n' => unescaped.push(b'\n'),
+                b'r' => unescaped.push(b'\r'),
+                _ => return None,
+            }
+        } else {
+            unescaped.push(b);
+        }
+    }
+
+    Some(unescaped)
+}
+
+/// Format the output line for the digest of the file `name`, not including the line terminator.
+fn format_line(options: &Options, name: &[u8], digest: &[u8]) -> Vec<u8> {
+    let (name, escaped) = if options.zero {
+        (name.to_vec(), false)
+    } else {
+        escape_name(name)
+    };
+    let mut line = if escaped { b"\\".to_vec() } else { Vec::new() };
+
+    if options.tag {
+        let algorithm = if digest.len() == OUTBYTES {
+            ALGORITHM.to_string()
+        } else {
+            format!("{}-{}", ALGORITHM, digest.len() * 8)
+        };
+        line.extend_from_slice(format!("{} (", algorithm).as_bytes());
+        line.extend_from_slice(&name);
+        line.extend_from_slice(format!(") = {}", to_hex(digest)).as_bytes());
+    } else {
+        let mode = if options.binary { '*' } else { ' ' };
+        line.extend_from_slice(format!("{} {}", to_hex(digest), mode).as_bytes());
+        line.extend_from_slice(&name);
+    }
+
+    line
+}
+
+/// Parse a BSD-style line of the form `BLAKE2b[-BITS] (NAME) = HEX`.
+fn parse_tagged_line(line: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let rest = line.strip_prefix(ALGORITHM.as_bytes())?;
+    let (bits, rest) = match rest.strip_prefix(b"-") {
+        Some(rest) => {
+            let end = rest.iter().position(|b| !b.is_ascii_digit())?;
+            let bits = std::str::from_utf8(&rest[..end])
+                .ok()?
+                .parse::<usize>()
+                .ok()?;
+            (Some(bits), &rest[end..])
+        }
+        None => (None, rest),
+    };
+    let rest = rest.strip_prefix(b" (")?;
+    let split = rfind(rest, b") = ")?;
+    let (name, hex) = (&rest[..split], &rest[split + 4..]);
+
+    let digest = from_hex(hex)?;
+    let expected_length = match bits {
+        Some(bits) if bits == 0 || !bits.is_multiple_of(8) || bits > OUTBYTES * 8 => return None,
+        Some(bits) => bits / 8,
+        None => OUTBYTES,
+    };
+    if digest.len() != expected_length {
+        return None;
+    }
+
+    Some((digest, name.to_vec()))
+}
+
+/// Parse a line of the form `HEX  NAME`, `HEX *NAME` or `HEX NAME`.
+fn parse_untagged_line(line: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let split = line.iter().position(|&b| b == b' ')?;
+    let (hex, rest) = (&line[..split], &line[split + 1..]);
+    let name = match rest.first() {
+        Some(b' ') | Some(b'*') => &rest[1..],
+        _ => rest,
+    };
+
+    let digest = from_hex(hex)?;
+    if digest.is_empty() || digest.len() > OUTBYTES || name.is_empty() {
+        return None;
+    }
+
+    Some((digest, name.to_vec()))
+}
+
+/// Parse a line of a checksum file into the expected digest and the name of the file to check.
+fn parse_check_line(line: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (line, escaped) = match line.strip_prefix(b"\\") {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+    let (digest, name) = if line.starts_with(ALGORITHM.as_bytes()) {
+        parse_tagged_line(line)?
+    } else {
+        parse_untagged_line(line)?
+    };
+
+    if escaped {
+        Some((digest, unescape_name(&name)?))
+    } else {
+        Some((digest, name))
+    }
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+struct Program {
+    options: Options,
+    stdout: io::Stdout,
+}
+
+impl Program {
+    fn error(&self, message: &str) {
+        eprintln!("{}: {}", PROGRAM, message);
+    }
+
+    fn print(&self, line: &[u8], terminator: &[u8]) {
+        let mut stdout = self.stdout.lock();
+        // Nothing useful can be done if stdout has gone away; the exit status is unaffected, as
+        // with the coreutils tools when writing to a closed pipe.
+        let _ = stdout
+            .write_all(line)
+            .and_then(|_| stdout.write_all(terminator));
+    }
+
+    /// Print the digest of every file named on the command line, returning whether all of them
+    /// could be read.
+    fn digest_files(&self) -> bool {
+        let terminator: &[u8] = if self.options.zero { b"\0" } else { b"\n" };
+        let mut ok = true;
+
+        for file in &self.options.files {
+            let name = name_bytes(file);
+            match digest_file(&name, self.options.digest_length) {
+                Ok(digest) => self.print(&format_line(&self.options, &name, &digest), terminator),
+                Err(err) => {
+                    self.error(&format!(
+                        "{}: {}",
+                        file.to_string_lossy(),
+                        describe_error(&err)
+                    ));
+                    ok = false;
+                }
+            }
+        }
+
+        ok
+    }
+
+    /// Verify the digests listed in the checksum file `list_name`, returning whether every listed
+    /// file matched.
+    fn check_list(&self, list_name: &OsStr) -> bool {
+        let list_bytes = name_bytes(list_name);
+        let display_name = if list_bytes == b"-" {
+            "'standard input'".to_string()
+        } else {
+            list_name.to_string_lossy().into_owned()
+        };
+        let reader = match open(&list_bytes) {
+            Ok(reader) => BufReader::new(reader),
+            Err(err) => {
+                self.error(&format!("{}: {}", display_name, describe_error(&err)));
+                return false;
+            }
+        };
+
+        let mut properly_formatted = 0;
+        let mut improperly_formatted = 0;
+        let mut mismatched = 0;
+        let mut unreadable = 0;
+        let mut verified = 0;
+
+        for (index, line) in reader.split(b'\n').enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    self.error(&format!("{}: {}", display_name, describe_error(&err)));
+                    return false;
+                }
+            };
+            let line = line.strip_suffix(b"\r").unwrap_or(&line);
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+
+            let (expected, name) = match parse_check_line(line) {
+                Some(parsed) => parsed,
+                None => {
+                    improperly_formatted += 1;
+                    if self.options.warn {
+                        self.error(&format!(
+                            "{}: {}: improperly formatted {} checksum line",
+                            display_name,
+                            index + 1,
+                            ALGORITHM,
+                        ));
+                    }
+                    continue;
+                }
+            };
+            properly_formatted += 1;
+
+            // Only a line break forces the name to be escaped when reporting a result.
+            let (escaped_name, prefix): (Vec<u8>, &[u8]) = if name.contains(&b'\n') {
+                (escape_name(&name).0, b"\\")
+            } else {
+                (name.clone(), b"")
+            };
+
+            match digest_file(&name, expected.len()) {
+                Ok(digest) => {
+                    verified += 1;
+                    // Accept upper case digests by comparing bytes rather than hex strings.
+                    if digest == expected {
+                        if !self.options.quiet && !self.options.status {
+                            self.print(&[prefix, &escaped_name, b": OK"].concat(), b"\n");
+                        }
+                    } else {
+                        mismatched += 1;
+                        if !self.options.status {
+                            self.print(&[prefix, &escaped_name, b": FAILED"].concat(), b"\n");
+                        }
+                    }
+                }
+                Err(ref err)
+                    if self.options.ignore_missing && err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    unreadable += 1;
+                    self.error(&format!(
+                        "{}: {}",
+                        String::from_utf8_lossy(&name),
+                        describe_error(&err)
+                    ));
+                    if !self.options.status {
+                        self.print(
+                            &[prefix, &escaped_name, b": FAILED open or read"].concat(),
+                            b"\n",
+                        );
+                    }
+                }
+            }
+        }
+
+        if properly_formatted == 0 {
+            self.error(&format!(
+                "{}: no properly formatted checksum lines found",
+                display_name
+            ));
+            return false;
+        }
+
+        if !self.options.status {
+            if improperly_formatted > 0 {
+                self.error(&format!(
+                    "WARNING: {} {} improperly formatted",
+                    improperly_formatted,
+                    plural(improperly_formatted, "line is", "lines are"),
+                ));
+            }
+            if unreadable > 0 {
+                self.error(&format!(
+                    "WARNING: {} listed {} could not be read",
+                    unreadable,
+                    plural(unreadable, "file", "files"),
+                ));
+            }
+            if mismatched > 0 {
+                self.error(&format!(
+                    "WARNING: {} computed {} did NOT match",
+                    mismatched,
+                    plural(mismatched, "checksum", "checksums"),
+                ));
+            }
+        }
+        if self.options.ignore_missing && verified == 0 {
+            self.error(&format!("{}: no file was verified", display_name));
+            return false;
+        }
+
+        mismatched == 0 && unreadable == 0 && !(self.options.strict && improperly_formatted > 0)
+    }
+
+    fn run(&self) -> bool {
+        if self.options.check {
+            let mut ok = true;
+            for list_name in &self.options.files {
+                ok &= self.check_list(list_name);
+            }
+            ok
+        } else {
+            self.digest_files()
+        }
+    }
+}
+
+fn main() {
+    let options = match parse_args(env::args_os().skip(1)) {
+        Ok(options) => options,
+        Err(Exit::Help) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(Exit::Version) => {
+            println!("{} (blake2b-py) {}", PROGRAM, env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(Exit::Usage(message)) => {
+            eprintln!("{}: {}", PROGRAM, message);
+            eprintln!("Try '{} --help' for more information.", PROGRAM);
+            process::exit(1);
+        }
+        Err(Exit::Fatal(messages)) => {
+            for message in messages {
+                eprintln!("{}: {}", PROGRAM, message);
+            }
+            process::exit(1);
+        }
+    };
+
+    let program = Program {
+        options,
+        stdout: io::stdout(),
+    };
+    let ok = program.run();
+    let _ = program.stdout.lock().flush();
+
+    if !ok {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(args(&["-bl", "256", "--tag", "a", "--", "-c"])).unwrap();
+
+        assert!(options.binary);
+        assert!(options.tag);
+        assert!(!options.check);
+        assert_eq!(options.digest_length, 32);
+        assert_eq!(options.files, args(&["a", "-c"]));
+
+        assert_eq!(parse_args(args(&["--length=0"])).unwrap().digest_length, 64);
+        assert_eq!(parse_args(args(&["-l128"])).unwrap().files, args(&["-"]));
+    }
+
+    #[test]
+    fn test_parse_args_error() {
+        assert_eq!(
+            parse_args(args(&["-l", "7"])),
+            Err(Exit::Fatal(strings(&[
+                "invalid length: '7'",
+                "length is not a multiple of 8",
+            ]))),
+        );
+        assert_eq!(
+            parse_args(args(&["-l", "520"])),
+            Err(Exit::Fatal(strings(&[
+                "invalid length: '520'",
+                "maximum digest length for 'BLAKE2b' is 512 bits",
+            ]))),
+        );
+        assert!(parse_args(args(&["-c", "--tag"])).is_err());
+        assert!(parse_args(args(&["--strict"])).is_err());
+        assert!(parse_args(args(&["-x"])).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_args_non_utf8() {
+        let name = OsStr::from_bytes(b"a\xffb").to_os_string();
+        let options = parse_args(vec![OsString::from("-b"), name.clone()]).unwrap();
+
+        assert_eq!(options.files, vec![name]);
+        assert_eq!(name_bytes(&options.files[0]), b"a\xffb");
+        assert!(parse_args(vec![OsStr::from_bytes(b"-\xff").to_os_string()]).is_err());
+    }
+
+    #[test]
+    fn test_digest_reader() {
+        let digest = digest_reader(&b"abc"[..], OUTBYTES).unwrap();
+
+        assert_eq!(
+            to_hex(&digest),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        );
+    }
+
+    #[test]
+    fn test_format_line() {
+        let digest = [0xab; 4];
+        let mut options = Options::default();
+
+        assert_eq!(format_line(&options, b"a b", &digest), b"abababab  a b");
+        assert_eq!(
+            format_line(&options, b"a\nb", &digest),
+            b"\\abababab  a\\nb"
+        );
+        assert_eq!(format_line(&options, b"a\xff", &digest), b"abababab  a\xff");
+
+        options.binary = true;
+        assert_eq!(format_line(&options, b"a", &digest), b"abababab *a");
+
+        options.tag = true;
+        assert_eq!(
+            format_line(&options, b"a", &digest),
+            b"BLAKE2b-32 (a) = abababab"
+        );
+
+        options.zero = true;
+        assert_eq!(
+            format_line(&options, b"a\nb", &digest),
+            b"BLAKE2b-32 (a\nb) = abababab"
+        );
+    }
+
+    #[test]
+    fn test_parse_check_line() {
+        let expected = Some((vec![0xab; 4], b"a b".to_vec()));
+
+        assert_eq!(parse_check_line(b"abababab  a b"), expected);
+        assert_eq!(parse_check_line(b"abababab *a b"), expected);
+        assert_eq!(parse_check_line(b"ABABABAB a b"), expected);
+        assert_eq!(parse_check_line(b"BLAKE2b-32 (a b) = abababab"), expected);
+        assert_eq!(
+            parse_check_line(b"\\abababab  a\\\\b\\n"),
+            Some((vec![0xab; 4], b"a\\b\n".to_vec())),
+        );
+        assert_eq!(
+            parse_check_line(b"BLAKE2b-32 (a\xffb) = abababab"),
+            Some((vec![0xab; 4], b"a\xffb".to_vec())),
+        );
+
+        assert_eq!(parse_check_line(b"abababa  a"), None);
+        assert_eq!(parse_check_line(b"abababab  "), None);
+        assert_eq!(parse_check_line(b"BLAKE2b (a) = abababab"), None);
+        assert_eq!(parse_check_line(b"BLAKE2b-24 (a) = abababab"), None);
+        assert_eq!(parse_check_line(b"\\abababab  a\\tb"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_list_non_utf8_names() {
+        let dir = env::temp_dir().join(format!("b2sum-test-{}", process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = [dir.as_os_str().as_bytes(), b"/a\xffb"].concat();
+        std::fs::write(name_path(&name), b"abc").unwrap();
+
+        let options = Options {
+            digest_length: OUTBYTES,
+            ..Options::default()
+        };
+        let digest = digest_file(&name, OUTBYTES).unwrap();
+        let list = dir.join("list");
+        let mut line = format_line(&options, &name, &digest);
+        line.push(b'\n');
+        std::fs::write(&list, line).unwrap();
+
+        let program = Program {
+            options: Options {
+                check: true,
+                quiet: true,
+                ..options
+            },
+            stdout: io::stdout(),
+        };
+        let ok = program.check_list(list.as_os_str());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(ok);
+    }
+}
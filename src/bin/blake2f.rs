@@ -0,0 +1,304 @@
+//! Decode, run and explain calldata for the blake2 F compression precompile.
+//!
+//! See here: https://eips.ethereum.org/EIPS/eip-152
+//!
+//! Build it without the Python bindings:
+//!
+//! ```text
+//! cargo build --release --no-default-features --bin blake2f
+//! ```
+
+use std::env;
+use std::io::{self, Read};
+use std::process;
+use std::time::{Duration, Instant};
+
+use blake2b::blake2b::{decode_parameters, CompressArgs};
+
+const PROGRAM: &str = "blake2f";
+
+/// Gas charged by the precompile for each round of the compression function.
+const GAS_PER_ROUND: u64 = 1;
+
+const USAGE: &str = "\
+Usage: blake2f [OPTION]... [CALLDATA]
+Decode 213 bytes of hex encoded blake2 F precompile calldata, print its parameters and gas
+cost, then run the compression function and print its output and running time.
+
+With no CALLDATA, or when CALLDATA is -, read it from standard input.  Whitespace and a
+leading 0x are ignored.
+
+      --json     print the result as a JSON object
+      --no-run   only decode the calldata; don't run the compression function
+      --help     display this help and exit
+";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    json: bool,
+    run: bool,
+    calldata: Option<String>,
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        run: true,
+        ..Options::default()
+    };
+
+    for arg in args {
+        match &arg[..] {
+            "--json" => options.json = true,
+            "--no-run" => options.run = false,
+            "--help" => return Ok(None),
+            "-" => options.calldata = None,
+            _ if arg.starts_with('-') => return Err(format!("unrecognized option '{}'", arg)),
+            _ if options.calldata.is_some() => {
+                return Err(format!("unexpected argument '{}'", arg));
+            }
+            _ => options.calldata = Some(arg),
+        }
+    }
+
+    Ok(Some(options))
+}
+
+/// Decode the hex string `input`, ignoring whitespace and an optional `0x` prefix.
+fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input.split_whitespace().collect();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(&digits);
+
+    if let Some((i, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "calldata is not valid hex, got {:?} at offset {}",
+            c, i
+        ));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!(
+            "calldata should have an even number of hex digits, got: {}",
+            digits.len()
+        ));
+    }
+
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn gas_cost(rounds: usize) -> u64 {
+    rounds as u64 * GAS_PER_ROUND
+}
+
+/// The outcome of running the compression function over decoded calldata.
+struct Output {
+    result: [u8; 64],
+    elapsed: Duration,
+}
+
+fn compress(args: &CompressArgs) -> Output {
+    let start = Instant::now();
+    let result = args.compress();
+    let elapsed = start.elapsed();
+
+    Output { result, elapsed }
+}
+
+fn format_words(name: &str, words: &[u64], lines: &mut Vec<String>) {
+    for (i, word) in words.iter().enumerate() {
+        lines.push(format!("{:<8}0x{:016x}", format!("{}[{}]:", name, i), word));
+    }
+}
+
+fn format_text(args: &CompressArgs, output: Option<&Output>) -> String {
+    let CompressArgs { rounds, h, m, t, f } = args;
+    let mut lines = vec![format!("{:<8}{}", "rounds:", rounds)];
+    format_words("h", h, &mut lines);
+    format_words("m", m, &mut lines);
+    format_words("t", t, &mut lines);
+    lines.push(format!("{:<8}{}", "f:", f));
+    lines.push(format!("{:<8}{}", "gas:", gas_cost(*rounds)));
+
+    if let Some(output) = output {
+        lines.push(format!("{:<8}{}", "output:", to_hex(&output.result)));
+        lines.push(format!("{:<8}{:?}", "time:", output.elapsed));
+    }
+
+    lines.join("\n")
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
+
+/// Words are written as hex strings since JSON numbers can't hold every 64-bit value exactly.
+fn json_words(words: &[u64]) -> String {
+    let words: Vec<String> = words
+        .iter()
+        .map(|word| format!("\"0x{:016x}\"", word))
+        .collect();
+
+    format!("[{}]", words.join(", "))
+}
+
+fn format_json(args: &CompressArgs, output: Option<&Output>) -> String {
+    let CompressArgs { rounds, h, m, t, f } = args;
+    let mut fields = vec![
+        format!("\"rounds\": {}", rounds),
+        format!("\"h\": {}", json_words(h)),
+        format!("\"m\": {}", json_words(m)),
+        format!("\"t\": {}", json_words(t)),
+        format!("\"f\": {}", f),
+        format!("\"gas\": {}", gas_cost(*rounds)),
+    ];
+
+    if let Some(output) = output {
+        fields.push(format!("\"output\": \"{}\"", to_hex(&output.result)));
+        fields.push(format!("\"elapsed_ns\": {}", output.elapsed.as_nanos()));
+    }
+
+    format!("{{{}}}", fields.join(", "))
+}
+
+fn read_calldata(options: &Options) -> Result<String, String> {
+    match &options.calldata {
+        Some(calldata) => Ok(calldata.clone()),
+        None => {
+            let mut calldata = String::new();
+            io::stdin()
+                .read_to_string(&mut calldata)
+                .map_err(|err| format!("could not read standard input: {}", err))?;
+            Ok(calldata)
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<String, String> {
+    let input = decode_hex(&read_calldata(options)?)?;
+    let args = decode_parameters(&input)?;
+    let output = if options.run {
+        Some(compress(&args))
+    } else {
+        None
+    };
+
+    if options.json {
+        Ok(format_json(&args, output.as_ref()))
+    } else {
+        Ok(format_text(&args, output.as_ref()))
+    }
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("{}: {}", PROGRAM, message);
+            eprintln!("Try '{} --help' for more information.", PROGRAM);
+            process::exit(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(report) => println!("{}", report),
+        Err(message) => {
+            if options.json {
+                println!("{{\"error\": {}}}", json_string(&message));
+            } else {
+                eprintln!("{}: {}", PROGRAM, message);
+            }
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EIP-152 test vector 5
+    const CALLDATA: &str = "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001";
+    const OUTPUT: &str = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+
+    fn options(calldata: &str, json: bool) -> Options {
+        Options {
+            json,
+            run: true,
+            calldata: Some(calldata.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("0x00ff\n 10").unwrap(), vec![0x00, 0xff, 0x10]);
+        assert!(decode_hex("0x0g").is_err());
+        assert!(decode_hex("abc").is_err());
+    }
+
+    #[test]
+    fn test_run_text() {
+        let report = run(&options(CALLDATA, false)).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "rounds: 12");
+        assert_eq!(lines[1], "h[0]:   0x6a09e667f2bdc948");
+        assert_eq!(lines[9], "m[0]:   0x0000000000636261");
+        assert_eq!(lines[25], "t[0]:   0x0000000000000003");
+        assert_eq!(lines[27], "f:      true");
+        assert_eq!(lines[28], "gas:    12");
+        assert_eq!(lines[29], format!("output: {}", OUTPUT));
+        assert!(lines[30].starts_with("time:   "));
+    }
+
+    #[test]
+    fn test_run_json() {
+        let report = run(&options(&format!("0x{}", CALLDATA), true)).unwrap();
+
+        assert!(report.starts_with("{\"rounds\": 12, \"h\": [\"0x6a09e667f2bdc948\", "));
+        assert!(report.contains("\"f\": true, \"gas\": 12, "));
+        assert!(report.contains(&format!("\"output\": \"{}\"", OUTPUT)));
+    }
+
+    #[test]
+    fn test_run_error() {
+        assert_eq!(
+            run(&options(&CALLDATA[..424], false)),
+            Err(
+                "input length for blake2 F precompile should be exactly 213 bytes, got: 212".into()
+            ),
+        );
+        assert_eq!(
+            run(&options(&format!("{}02", &CALLDATA[..424]), false)),
+            Err("incorrect final block indicator flag, got: 2".into()),
+        );
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a \"b\"\n"), "\"a \\\"b\\\"\\u000a\"");
+    }
+}
@@ -1,7 +1,10 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
-const SIGMA_LEN: usize = 10;
-const SIGMA: [[usize; 16]; SIGMA_LEN] = [
+use crate::hasher::BLOCKBYTES;
+use crate::zeroize::zeroize;
+
+pub(crate) const SIGMA_LEN: usize = 10;
+pub(crate) const SIGMA: [[usize; 16]; SIGMA_LEN] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
     [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
     [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
@@ -15,9 +18,9 @@ const SIGMA: [[usize; 16]; SIGMA_LEN] = [
 ];
 
 const WORDBITS: usize = 64;
-const MASKBITS: u64 = u64::max_value();
+const MASKBITS: u64 = u64::MAX;
 
-const IV: [u64; 8] = [
+pub(crate) const IV: [u64; 8] = [
     0x6a09e667f3bcc908,
     0xbb67ae8584caa73b,
     0x3c6ef372fe94f82b,
@@ -39,7 +42,7 @@ fn u64_from_le(input: &[u8]) -> u64 {
 }
 
 #[inline]
-fn eight_words(input: &[u8]) -> [u64; 8] {
+pub(crate) fn eight_words(input: &[u8]) -> [u64; 8] {
     [
         u64_from_le(&input[..8]),
         u64_from_le(&input[8..16]),
@@ -53,7 +56,7 @@ fn eight_words(input: &[u8]) -> [u64; 8] {
 }
 
 #[inline]
-fn sixteen_words(input: &[u8]) -> [u64; 16] {
+pub(crate) fn sixteen_words(input: &[u8]) -> [u64; 16] {
     [
         u64_from_le(&input[..8]),
         u64_from_le(&input[8..16]),
@@ -79,7 +82,86 @@ fn two_words(input: &[u8]) -> [u64; 2] {
     [u64_from_le(&input[..8]), u64_from_le(&input[8..16])]
 }
 
-pub type CompressArgs = (usize, [u64; 8], [u64; 16], [u64; 2], bool);
+/// Input to the compression function `F`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressArgs {
+    /// The number of rounds of mixing.
+    pub rounds: usize,
+    /// The starting state.
+    pub h: [u64; 8],
+    /// The message block.
+    pub m: [u64; 16],
+    /// The message byte offset at the end of the block.
+    pub t: [u64; 2],
+    /// The final block flag.
+    pub f: bool,
+}
+
+impl CompressArgs {
+    /// Decode the tightly packed encoding of blake2 precompile input in `input`.  The same as
+    /// `decode_parameters`.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, String> {
+        decode_parameters(input)
+    }
+
+    /// Input for compressing the 128 raw bytes in `block`, read as 16 little endian words.
+    pub fn block(
+        rounds: usize,
+        h: [u64; 8],
+        block: &[u8],
+        t: [u64; 2],
+        f: bool,
+    ) -> Result<Self, String> {
+        if block.len() != BLOCKBYTES {
+            return Err(format!(
+                "message block should be exactly {} bytes, got: {}",
+                BLOCKBYTES,
+                block.len()
+            ));
+        }
+
+        Ok(CompressArgs {
+            rounds,
+            h,
+            m: sixteen_words(block),
+            t,
+            f,
+        })
+    }
+
+    /// The tightly packed encoding read by `from_bytes`.  Panics if `rounds` doesn't fit in 32
+    /// bits.
+    pub fn to_bytes(&self) -> [u8; 213] {
+        let rounds = u32::try_from(self.rounds).expect("number of rounds should fit in 32 bits");
+
+        let mut out = [0; 213];
+        out[..4].copy_from_slice(&rounds.to_be_bytes());
+        let words = self.h.iter().chain(self.m.iter()).chain(self.t.iter());
+        for (chunk, word) in out[4..212].chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out[212] = self.f as u8;
+
+        out
+    }
+
+    /// Run the compression function `F` over this input.
+    pub fn compress(&self) -> [u8; 64] {
+        F(self.rounds, &self.h, &self.m, &self.t, self.f)
+    }
+}
+
+impl From<(usize, [u64; 8], [u64; 16], [u64; 2], bool)> for CompressArgs {
+    fn from((rounds, h, m, t, f): (usize, [u64; 8], [u64; 16], [u64; 2], bool)) -> Self {
+        CompressArgs { rounds, h, m, t, f }
+    }
+}
+
+impl From<CompressArgs> for (usize, [u64; 8], [u64; 16], [u64; 2], bool) {
+    fn from(args: CompressArgs) -> Self {
+        (args.rounds, args.h, args.m, args.t, args.f)
+    }
+}
 
 /// Decode blake2 precompile input parameters from the tightly packed encoding in the byte sequence
 /// `input`.
@@ -94,10 +176,10 @@ pub fn decode_parameters(input: &[u8]) -> Result<CompressArgs, String> {
     }
 
     let rounds = u32::from_be_bytes((&input[..4]).try_into().unwrap()) as usize;
-    let starting_state = eight_words(&input[4..68]);
-    let block = sixteen_words(&input[68..196]);
-    let offset_counters = two_words(&input[196..212]);
-    let final_block_flag = match input[212] {
+    let h = eight_words(&input[4..68]);
+    let m = sixteen_words(&input[68..196]);
+    let t = two_words(&input[196..212]);
+    let f = match input[212] {
         0 => false,
         1 => true,
         x => {
@@ -105,13 +187,7 @@ pub fn decode_parameters(input: &[u8]) -> Result<CompressArgs, String> {
         }
     };
 
-    Ok((
-        rounds,
-        starting_state,
-        block,
-        offset_counters,
-        final_block_flag,
-    ))
+    Ok(CompressArgs { rounds, h, m, t, f })
 }
 
 /// Rotate bits in the unsigned 64-bit integer `x` to the right by `n` bits.
@@ -122,24 +198,64 @@ fn rotate_bits(x: u64, n: usize) -> u64 {
     (x >> n) ^ (x << (WORDBITS - n))
 }
 
-/// The blake2b mixing function G.
+/// The blake2b mixing function G, which mixes the message words `x` and `y` into the words `a`,
+/// `b`, `c` and `d` of the working vector `v`.  Panics if an index is out of bounds.
 ///
 /// See here: https://tools.ietf.org/html/rfc7693#section-3.1
 #[allow(non_snake_case)]
 #[inline]
-fn G(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
-    // RFC 7693 includes the use of mod operations with operand 2 ** 64.  We omit those because we
-    // get them for free with u64 arithmetic.
-    v[a] = v[a] + v[b] + x;
+pub fn G(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    // RFC 7693 includes the use of mod operations with operand 2 ** 64.  Wrapping u64 arithmetic
+    // gives us those for free, whether or not overflow checks are enabled.
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
     v[d] = rotate_bits(v[d] ^ v[a], ROT1);
-    v[c] = v[c] + v[d];
+    v[c] = v[c].wrapping_add(v[d]);
     v[b] = rotate_bits(v[b] ^ v[c], ROT2);
-    v[a] = v[a] + v[b] + y;
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
     v[d] = rotate_bits(v[d] ^ v[a], ROT3);
-    v[c] = v[c] + v[d];
+    v[c] = v[c].wrapping_add(v[d]);
     v[b] = rotate_bits(v[b] ^ v[c], ROT4);
 }
 
+// Always inlined so that the loop in `compress` compiles to the same code as with the `G` calls
+// written out in it.
+#[inline(always)]
+fn mix_round(v: &mut [u64; 16], m: &[u64], r: usize) {
+    let s = &SIGMA[r % SIGMA_LEN];
+
+    G(v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+    G(v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+    G(v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+    G(v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+
+    G(v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+    G(v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+    G(v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+    G(v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+}
+
+/// Round `r` of the blake2b compression function: `G` applied to the columns and then the
+/// diagonals of the working vector `v`, with the message words of `m` in the order given by row
+/// `r % 10` of the message schedule.  `F` runs this `rounds` times between setting up the
+/// working vector and feeding it forward into the output.
+///
+/// See here: https://tools.ietf.org/html/rfc7693#section-3.2
+pub fn round(v: &mut [u64; 16], m: &[u64; 16], r: usize) {
+    mix_round(v, m, r);
+}
+
+/// The keyless blake2b permutation of the 16 word state `v`: `rounds` rounds with an all zero
+/// message block.  Each round only reorders message words, so without a message every round is
+/// the same invertible mixing of `v`.  There is no initialization or feed-forward, so this is a
+/// building block for sponge constructions rather than a hash.
+pub fn permute(v: &mut [u64; 16], rounds: usize) {
+    const ZEROS: [u64; 16] = [0; 16];
+
+    for r in 0..rounds {
+        mix_round(v, &ZEROS, r);
+    }
+}
+
 /// The blake2b compression function F.
 ///
 /// See here: https://tools.ietf.org/html/rfc7693#section-3.2
@@ -150,12 +266,93 @@ pub fn F(
     block: &[u64],
     offset_counters: &[u64],
     final_block_flag: bool,
+) -> [u8; 64] {
+    compress(
+        rounds,
+        starting_state,
+        block,
+        offset_counters,
+        final_block_flag,
+        false,
+    )
+}
+
+/// The blake2b compression function F with the last node flag `f1` set as well, for the final
+/// block of the last node at each depth of a hash tree.
+///
+/// See here: https://blake2.net/blake2.pdf (section 2.3)
+#[allow(non_snake_case)]
+pub(crate) fn F_last_node(
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+) -> [u8; 64] {
+    compress(
+        rounds,
+        starting_state,
+        block,
+        offset_counters,
+        final_block_flag,
+        true,
+    )
+}
+
+// Always inlined so that `F`, which never sets the last node flag, compiles to the same code as
+// it would without it.
+#[inline(always)]
+fn compress(
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+    last_node_flag: bool,
 ) -> [u8; 64] {
     let h = starting_state;
     let m = block;
-    let t = offset_counters;
 
-    let mut v = [
+    let mut v = working_vector(h, offset_counters, final_block_flag, last_node_flag);
+    for r in 0..rounds {
+        mix_round(&mut v, m, r);
+    }
+
+    feed_forward(h, &mut v)
+}
+
+/// The blake2b compression function F, run `batch` rounds at a time.  `keep_going` is called
+/// between batches, and returning `false` abandons the call, in which case `None` is returned.
+/// Used to enforce a deadline without slowing down `F` itself.
+#[allow(non_snake_case)]
+pub(crate) fn F_batched(
+    args: &CompressArgs,
+    batch: usize,
+    mut keep_going: impl FnMut() -> bool,
+) -> Option<[u8; 64]> {
+    let mut v = working_vector(&args.h, &args.t, args.f, false);
+
+    let mut r = 0;
+    while r < args.rounds {
+        if r > 0 && !keep_going() {
+            zeroize(&mut v);
+            return None;
+        }
+        let end = args.rounds.min(r.saturating_add(batch.max(1)));
+        for r in r..end {
+            mix_round(&mut v, &args.m, r);
+        }
+        r = end;
+    }
+
+    Some(feed_forward(&args.h, &mut v))
+}
+
+/// Set up the working vector of the compression function from the starting state `h`, offset
+/// counters `t` and flags.
+#[inline(always)]
+fn working_vector(h: &[u64], t: &[u64], final_block_flag: bool, last_node_flag: bool) -> [u64; 16] {
+    [
         h[0],         // 0
         h[1],         // 1
         h[2],         // 2
@@ -175,23 +372,17 @@ pub fn F(
         } else {
             IV[6]
         }, // 14
-        IV[7],        // 15
-    ];
-
-    for r in 0..rounds {
-        let s = &SIGMA[r % SIGMA_LEN];
-
-        G(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
-        G(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
-        G(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
-        G(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
-
-        G(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
-        G(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
-        G(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
-        G(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
-    }
+        if last_node_flag {
+            MASKBITS ^ IV[7]
+        } else {
+            IV[7]
+        }, // 15
+    ]
+}
 
+/// Feed the working vector `v` forward into the output of the compression function, then wipe it.
+#[inline(always)]
+fn feed_forward(h: &[u64], v: &mut [u64; 16]) -> [u8; 64] {
     let result_words = [
         (h[0] ^ v[0] ^ v[8]).to_le_bytes(),
         (h[1] ^ v[1] ^ v[9]).to_le_bytes(),
@@ -204,15 +395,229 @@ pub fn F(
     ];
 
     let mut result = [0u8; 64];
-    for (i, word_bytes) in result_words.into_iter().enumerate() {
-        for (j, x) in word_bytes.into_iter().enumerate() {
+    for (i, word_bytes) in result_words.iter().enumerate() {
+        for (j, x) in word_bytes.iter().enumerate() {
             result[i * 8 + j] = *x;
         }
     }
 
+    // The working vector is derived from the message block, which may hold key material
+    zeroize(v);
+
     result
 }
 
+/// Check that the starting state, block and offset counter vectors passed to the blake2b
+/// compression function have the right lengths.
+pub(crate) fn check_lengths(
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+) -> Result<(), String> {
+    if starting_state.len() != 8 {
+        return Err(format!(
+            "starting state vector must have length 8, got: {}",
+            starting_state.len(),
+        ));
+    }
+    if block.len() != 16 {
+        return Err(format!(
+            "block vector must have length 16, got: {}",
+            block.len(),
+        ));
+    }
+    if offset_counters.len() != 2 {
+        return Err(format!(
+            "offset counters vector must have length 2, got: {}",
+            offset_counters.len(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run the blake2b compression function F after checking that the starting state, block and
+/// offset counter vectors have the right lengths.
+pub fn checked_compress(
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+) -> Result<[u8; 64], String> {
+    check_lengths(starting_state, block, offset_counters)?;
+
+    Ok(F(
+        rounds,
+        starting_state,
+        block,
+        offset_counters,
+        final_block_flag,
+    ))
+}
+
+/// The `(a, b, c, d)` working vector indices mixed by each of the eight `G` calls in a round, in
+/// the order `F` makes them.
+const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// How often a `Trace` reports the working vector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceLevel {
+    /// After every call to `G`, eight times per round.
+    G,
+    /// After every round.
+    Round,
+}
+
+/// A snapshot of the working vector `v` taken by a `Trace`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The round the snapshot was taken in, starting from 0.
+    pub round: usize,
+    /// The `G` call, from 0 to 7, that was just made.  `None` for round level traces.
+    pub g: Option<usize>,
+    /// The SIGMA row used to select message words in this round.
+    pub sigma: [usize; 16],
+    /// The working vector after the step.
+    pub v: [u64; 16],
+}
+
+/// An iterator that runs the blake2b compression function F one step at a time, yielding the
+/// working vector after each step.  Created by `F_trace`.
+///
+/// This is a separate, slower copy of the `F` loop meant for debugging disagreements with other
+/// implementations; `F` itself is left untouched.
+pub struct Trace {
+    level: TraceLevel,
+    rounds: usize,
+    h: [u64; 8],
+    m: [u64; 16],
+    v: [u64; 16],
+    round: usize,
+    g: usize,
+}
+
+impl Trace {
+    /// The output of the compression function given the working vector so far.  Once the trace
+    /// has been exhausted this is the same as the output of `F`.
+    pub fn output(&self) -> [u8; 64] {
+        let mut result = [0u8; 64];
+        for i in 0..8 {
+            let word = self.h[i] ^ self.v[i] ^ self.v[i + 8];
+            result[i * 8..i * 8 + 8].copy_from_slice(&word.to_le_bytes());
+        }
+
+        result
+    }
+
+    fn mix(&mut self, s: &[usize; 16], g: usize) {
+        let [a, b, c, d] = G_INDICES[g];
+        G(
+            &mut self.v,
+            a,
+            b,
+            c,
+            d,
+            self.m[s[2 * g]],
+            self.m[s[2 * g + 1]],
+        );
+    }
+}
+
+impl Iterator for Trace {
+    type Item = TraceStep;
+
+    fn next(&mut self) -> Option<TraceStep> {
+        if self.round >= self.rounds {
+            return None;
+        }
+
+        let round = self.round;
+        let sigma = SIGMA[round % SIGMA_LEN];
+        let g = match self.level {
+            TraceLevel::G => {
+                let g = self.g;
+                self.mix(&sigma, g);
+                Some(g)
+            }
+            TraceLevel::Round => {
+                for g in 0..8 {
+                    self.mix(&sigma, g);
+                }
+                None
+            }
+        };
+
+        if g.is_none() || self.g == 7 {
+            self.round += 1;
+            self.g = 0;
+        } else {
+            self.g += 1;
+        }
+
+        Some(TraceStep {
+            round,
+            g,
+            sigma,
+            v: self.v,
+        })
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        // The working vector and message block may hold key material
+        zeroize(&mut self.v);
+        zeroize(&mut self.m);
+    }
+}
+
+/// Run the blake2b compression function F step by step, reporting the working vector `v` after
+/// each call to `G` or after each round depending on `level`.  The arguments are the same as for
+/// `F`, whose output is available from `Trace::output` once the trace has been exhausted.
+///
+/// Panics if the starting state, block or offset counter vectors don't have lengths 8, 16 and 2.
+#[allow(non_snake_case)]
+pub fn F_trace(
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+    level: TraceLevel,
+) -> Trace {
+    let h: [u64; 8] = starting_state.try_into().unwrap();
+    let t = offset_counters;
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if final_block_flag {
+        v[14] ^= MASKBITS;
+    }
+
+    Trace {
+        level,
+        rounds,
+        h,
+        m: block.try_into().unwrap(),
+        v,
+        round: 0,
+        g: 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,7 +664,13 @@ mod tests {
         for (inp, expected) in FAST_EXAMPLES {
             let input_bytes = hex::decode(inp).unwrap();
             let blake2_params = decode_parameters(&input_bytes).unwrap();
-            let (rounds, starting_state, block, offset_counters, final_block_flag) = blake2_params;
+            let CompressArgs {
+                rounds,
+                h: starting_state,
+                m: block,
+                t: offset_counters,
+                f: final_block_flag,
+            } = blake2_params;
 
             let result_bytes = F(
                 rounds,
@@ -279,7 +690,13 @@ mod tests {
         for (inp, expected) in SLOW_EXAMPLES {
             let input_bytes = hex::decode(inp).unwrap();
             let blake2_params = decode_parameters(&input_bytes).unwrap();
-            let (rounds, starting_state, block, offset_counters, final_block_flag) = blake2_params;
+            let CompressArgs {
+                rounds,
+                h: starting_state,
+                m: block,
+                t: offset_counters,
+                f: final_block_flag,
+            } = blake2_params;
 
             let result_bytes = F(
                 rounds,
@@ -306,7 +723,13 @@ mod tests {
 
         let input_bytes = hex::decode(inp).unwrap();
         let blake2_params = decode_parameters(&input_bytes).unwrap();
-        let (rounds, starting_state, block, offset_counters, final_block_flag) = blake2_params;
+        let CompressArgs {
+            rounds,
+            h: starting_state,
+            m: block,
+            t: offset_counters,
+            f: final_block_flag,
+        } = blake2_params;
 
         let t_start = std::time::SystemTime::now();
 
@@ -336,46 +759,177 @@ mod tests {
             }
         }
     }
-}
 
-#[cfg(test)]
-mod bench {
-    extern crate test;
+    #[test]
+    fn test_compress_args_round_trip() {
+        for (inp, expected) in FAST_EXAMPLES {
+            let input_bytes = hex::decode(inp).unwrap();
+            let args = CompressArgs::from_bytes(&input_bytes).unwrap();
 
-    use super::*;
+            assert_eq!(args.to_bytes().to_vec(), input_bytes);
+            assert_eq!(hex::encode(args.compress()), *expected);
 
-    use test::Bencher;
+            let tuple: (usize, [u64; 8], [u64; 16], [u64; 2], bool) = args.into();
+            assert_eq!(CompressArgs::from(tuple), args);
 
-    fn rounds_benchmark(rounds: usize, bencher: &mut Bencher) {
-        let input_bytes = hex::decode("0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001").unwrap();
+            let mut block = [0; BLOCKBYTES];
+            block.copy_from_slice(&input_bytes[68..196]);
+            assert_eq!(
+                CompressArgs::block(args.rounds, args.h, &block, args.t, args.f),
+                Ok(args)
+            );
+        }
+    }
 
-        let blake2_params = decode_parameters(&input_bytes).unwrap();
-        let (_, starting_state, block, offset_counters, final_block_flag) = blake2_params;
+    #[test]
+    fn test_compress_args_block_length() {
+        assert!(CompressArgs::block(12, IV, &[0; 127], [0; 2], false).is_err());
+        assert!(CompressArgs::block(12, IV, &[0; 129], [0; 2], false).is_err());
+    }
 
-        bencher.iter(|| {
-            F(
-                rounds,
-                &starting_state,
-                &block,
-                &offset_counters,
-                final_block_flag,
-            )
-            .to_vec()
+    #[test]
+    fn test_f_batched_matches_f() {
+        for (inp, expected) in FAST_EXAMPLES {
+            let args = decode_parameters(&hex::decode(inp).unwrap()).unwrap();
+            for &batch in &[0, 1, 5, 12, 100] {
+                let output = F_batched(&args, batch, || true).unwrap();
+                assert_eq!(hex::encode(output), *expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_f_batched_stops() {
+        let args = decode_parameters(&hex::decode(FAST_EXAMPLES[1].0).unwrap()).unwrap();
+        let mut checks = 0;
+
+        // 12 rounds in batches of 5 check twice, and stopping at the second check gives up
+        let output = F_batched(&args, 5, || {
+            checks += 1;
+            checks < 2
         });
+        assert_eq!((output, checks), (None, 2));
     }
 
-    #[bench]
-    fn bench_100_000_rounds(bencher: &mut Bencher) {
-        rounds_benchmark(100_000, bencher);
+    /// Set up the working vector as `F` does, for checking `F` against `round` and `permute`
+    fn init_v(h: &[u64; 8], t: &[u64; 2], f: bool) -> [u64; 16] {
+        let mut v = [0; 16];
+        v[..8].copy_from_slice(h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= t[0];
+        v[13] ^= t[1];
+        if f {
+            v[14] = !v[14];
+        }
+
+        v
     }
 
-    #[bench]
-    fn bench_2_000_000_rounds(bencher: &mut Bencher) {
-        rounds_benchmark(2_000_000, bencher);
+    fn feed_forward(h: &[u64; 8], v: &[u64; 16]) -> Vec<u8> {
+        (0..8)
+            .flat_map(|i| (h[i] ^ v[i] ^ v[i + 8]).to_le_bytes().to_vec())
+            .collect()
     }
 
-    #[bench]
-    fn bench_8_000_000_rounds(bencher: &mut Bencher) {
-        rounds_benchmark(8_000_000, bencher);
+    #[test]
+    fn test_round_matches_f() {
+        for (inp, expected) in FAST_EXAMPLES {
+            let input_bytes = hex::decode(inp).unwrap();
+            let CompressArgs { rounds, h, m, t, f } = decode_parameters(&input_bytes).unwrap();
+
+            let mut v = init_v(&h, &t, f);
+            for r in 0..rounds {
+                round(&mut v, &m, r);
+            }
+
+            assert_eq!(hex::encode(feed_forward(&h, &v)), *expected);
+        }
+    }
+
+    #[test]
+    fn test_round_is_g_on_columns_then_diagonals() {
+        let m: [u64; 16] = sixteen_words(&(0..128).collect::<Vec<u8>>());
+        let s = &SIGMA[3];
+
+        let mut expected = init_v(&IV, &[128, 0], true);
+        let mut v = expected;
+        let indices = [
+            (0, 4, 8, 12),
+            (1, 5, 9, 13),
+            (2, 6, 10, 14),
+            (3, 7, 11, 15),
+            (0, 5, 10, 15),
+            (1, 6, 11, 12),
+            (2, 7, 8, 13),
+            (3, 4, 9, 14),
+        ];
+        for (i, &(a, b, c, d)) in indices.iter().enumerate() {
+            G(&mut expected, a, b, c, d, m[s[2 * i]], m[s[2 * i + 1]]);
+        }
+
+        round(&mut v, &m, 13);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn test_permute_matches_f() {
+        for (inp, _) in FAST_EXAMPLES {
+            let input_bytes = hex::decode(inp).unwrap();
+            let CompressArgs {
+                rounds, h, t, f, ..
+            } = decode_parameters(&input_bytes).unwrap();
+
+            let mut v = init_v(&h, &t, f);
+            permute(&mut v, rounds);
+
+            assert_eq!(
+                feed_forward(&h, &v),
+                F(rounds, &h, &[0; 16], &t, f).to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_checked_compress_error() {
+        assert!(checked_compress(12, &[0; 7], &[0; 16], &[0; 2], true).is_err());
+        assert!(checked_compress(12, &[0; 8], &[0; 17], &[0; 2], true).is_err());
+        assert!(checked_compress(12, &[0; 8], &[0; 16], &[0; 1], true).is_err());
+        assert!(checked_compress(12, &[0; 8], &[0; 16], &[0; 2], true).is_ok());
+    }
+
+    #[test]
+    fn test_f_trace() {
+        for (inp, expected) in FAST_EXAMPLES {
+            let input_bytes = hex::decode(inp).unwrap();
+            let CompressArgs { rounds, h, m, t, f } = decode_parameters(&input_bytes).unwrap();
+
+            let mut trace = F_trace(rounds, &h, &m, &t, f, TraceLevel::G);
+            let steps: Vec<TraceStep> = trace.by_ref().collect();
+            assert_eq!(steps.len(), rounds * 8);
+            assert_eq!(hex::encode(trace.output()), *expected);
+
+            let mut trace = F_trace(rounds, &h, &m, &t, f, TraceLevel::Round);
+            let rounds_steps: Vec<TraceStep> = trace.by_ref().collect();
+            assert_eq!(rounds_steps.len(), rounds);
+            assert_eq!(hex::encode(trace.output()), *expected);
+
+            // Every eighth G level step ends a round
+            for (round, step) in rounds_steps.iter().enumerate() {
+                let g_step = &steps[round * 8 + 7];
+                assert_eq!((step.round, step.g), (round, None));
+                assert_eq!((g_step.round, g_step.g), (round, Some(7)));
+                assert_eq!(step.sigma, SIGMA[round % SIGMA_LEN]);
+                assert_eq!(step.v, g_step.v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_f_trace_rounds_wrap_sigma() {
+        let steps: Vec<TraceStep> =
+            F_trace(12, &IV, &[0; 16], &[0; 2], false, TraceLevel::Round).collect();
+
+        assert_eq!(steps[10].sigma, SIGMA[0]);
+        assert_eq!(steps[11].sigma, SIGMA[1]);
     }
 }
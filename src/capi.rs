@@ -0,0 +1,257 @@
+//! A C ABI over the blake2 F compression function and the streaming hasher.
+//!
+//! The matching header is `include/blake2b.h`, which is generated from this module with
+//! `make header`.  Every function returning an `int32_t` returns `BLAKE2B_OK` on success or one of
+//! the other `BLAKE2B_ERR_*` codes on failure, in which case its outputs are left untouched.
+
+use std::slice;
+
+use crate::blake2b::{decode_parameters, CompressArgs, F};
+use crate::hasher::{Blake2b, Params};
+
+/// The call succeeded.
+pub const BLAKE2B_OK: i32 = 0;
+/// A required pointer argument was NULL.
+pub const BLAKE2B_ERR_NULL_POINTER: i32 = -1;
+/// The precompile input was not exactly `BLAKE2B_F_INPUT_BYTES` bytes long.
+pub const BLAKE2B_ERR_INPUT_LENGTH: i32 = -2;
+/// The final block indicator flag of the precompile input was neither 0 nor 1.
+pub const BLAKE2B_ERR_FINAL_BLOCK_FLAG: i32 = -3;
+/// An output buffer length didn't match the digest length of the hasher.
+pub const BLAKE2B_ERR_OUTPUT_LENGTH: i32 = -4;
+
+/// Number of bytes in the tightly packed input to the blake2 F precompile.
+pub const BLAKE2B_F_INPUT_BYTES: usize = 213;
+/// Number of bytes in the output of the blake2 F compression function.
+pub const BLAKE2B_F_OUTPUT_BYTES: usize = 64;
+
+/// An opaque streaming blake2b hashing state.  Create one with `blake2b_hasher_new` and release
+/// it with either `blake2b_hasher_finalize` or `blake2b_hasher_free`.
+#[allow(non_camel_case_types)]
+pub struct blake2b_hasher(Blake2b);
+
+/// Borrow `len` bytes at `data` as a slice.  A NULL `data` is only allowed when `len` is zero.
+unsafe fn checked_slice<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Run `rounds` rounds of the blake2b compression function F over the state vector `h`, message
+/// block vector `m` and offset counter vector `t`, with final block indicator flag `f`, and write
+/// the 64 byte result to `out`.
+///
+/// # Safety
+///
+/// `h`, `m`, `t` and `out` must point to 8, 16, 2 and 64 valid elements respectively.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_f(
+    rounds: u32,
+    h: *const u64,
+    m: *const u64,
+    t: *const u64,
+    f: bool,
+    out: *mut u8,
+) -> i32 {
+    if h.is_null() || m.is_null() || t.is_null() || out.is_null() {
+        return BLAKE2B_ERR_NULL_POINTER;
+    }
+
+    let result = F(
+        rounds as usize,
+        slice::from_raw_parts(h, 8),
+        slice::from_raw_parts(m, 16),
+        slice::from_raw_parts(t, 2),
+        f,
+    );
+    slice::from_raw_parts_mut(out, BLAKE2B_F_OUTPUT_BYTES).copy_from_slice(&result);
+
+    BLAKE2B_OK
+}
+
+/// Decode `input_len` bytes of tightly packed blake2 F precompile input at `input`, run the
+/// compression function over them and write the 64 byte result to `out`.
+///
+/// See here: https://eips.ethereum.org/EIPS/eip-152#specification
+///
+/// # Safety
+///
+/// `input` must point to `input_len` valid bytes and `out` to 64 valid bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_decode_and_compress(
+    input: *const u8,
+    input_len: usize,
+    out: *mut u8,
+) -> i32 {
+    let input = match checked_slice(input, input_len) {
+        Some(input) if !out.is_null() => input,
+        _ => return BLAKE2B_ERR_NULL_POINTER,
+    };
+    if input.len() != BLAKE2B_F_INPUT_BYTES {
+        return BLAKE2B_ERR_INPUT_LENGTH;
+    }
+
+    // With the length checked, the final block flag is the only thing left to reject
+    let CompressArgs { rounds, h, m, t, f } = match decode_parameters(input) {
+        Ok(args) => args,
+        Err(_) => return BLAKE2B_ERR_FINAL_BLOCK_FLAG,
+    };
+    let result = F(rounds, &h, &m, &t, f);
+    slice::from_raw_parts_mut(out, BLAKE2B_F_OUTPUT_BYTES).copy_from_slice(&result);
+
+    BLAKE2B_OK
+}
+
+/// Create a streaming hasher producing `digest_length` byte digests, keyed with the `key_len`
+/// bytes at `key`.  Pass a NULL `key` and a `key_len` of 0 for unkeyed hashing.  Returns NULL if
+/// `digest_length` isn't between 1 and 64 or `key_len` is greater than 64.
+///
+/// # Safety
+///
+/// `key` must point to `key_len` valid bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_hasher_new(
+    digest_length: usize,
+    key: *const u8,
+    key_len: usize,
+) -> *mut blake2b_hasher {
+    let key = match checked_slice(key, key_len) {
+        Some(key) => key,
+        None => return std::ptr::null_mut(),
+    };
+    let params = Params {
+        digest_length,
+        key: key.to_vec(),
+        ..Params::default()
+    };
+
+    match Blake2b::with_params(&params) {
+        Ok(state) => Box::into_raw(Box::new(blake2b_hasher(state))),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Absorb the `input_len` bytes at `input` into `hasher`.
+///
+/// # Safety
+///
+/// `hasher` must have been returned by `blake2b_hasher_new` and not yet released, and `input`
+/// must point to `input_len` valid bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_hasher_update(
+    hasher: *mut blake2b_hasher,
+    input: *const u8,
+    input_len: usize,
+) -> i32 {
+    match (hasher.as_mut(), checked_slice(input, input_len)) {
+        (Some(hasher), Some(input)) => {
+            hasher.0.update(input);
+            BLAKE2B_OK
+        }
+        _ => BLAKE2B_ERR_NULL_POINTER,
+    }
+}
+
+/// Write the digest of all input absorbed by `hasher` to the `out_len` bytes at `out`, then
+/// release `hasher`.  `out_len` must equal the digest length `hasher` was created with.  `hasher`
+/// is released even if an error is returned.
+///
+/// # Safety
+///
+/// `hasher` must have been returned by `blake2b_hasher_new` and not yet released, and `out` must
+/// point to `out_len` valid bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_hasher_finalize(
+    hasher: *mut blake2b_hasher,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    if hasher.is_null() {
+        return BLAKE2B_ERR_NULL_POINTER;
+    }
+    let hasher = Box::from_raw(hasher);
+    if out.is_null() {
+        return BLAKE2B_ERR_NULL_POINTER;
+    }
+
+    let digest = hasher.0.finalize();
+    if out_len != digest.len() {
+        return BLAKE2B_ERR_OUTPUT_LENGTH;
+    }
+    slice::from_raw_parts_mut(out, out_len).copy_from_slice(&digest);
+
+    BLAKE2B_OK
+}
+
+/// Release `hasher` without computing its digest.  Passing NULL does nothing.
+///
+/// # Safety
+///
+/// `hasher` must be NULL or have been returned by `blake2b_hasher_new` and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn blake2b_hasher_free(hasher: *mut blake2b_hasher) {
+    if !hasher.is_null() {
+        drop(Box::from_raw(hasher));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EIP-152 test vector 5
+    const INPUT: &str = "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001";
+    const OUTPUT: &str = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+
+    #[test]
+    fn test_capi_decode_and_compress() {
+        let mut input = hex::decode(INPUT).unwrap();
+        let mut out = [0u8; 64];
+
+        unsafe {
+            let code = blake2b_decode_and_compress(input.as_ptr(), input.len(), out.as_mut_ptr());
+            assert_eq!(code, BLAKE2B_OK);
+            assert_eq!(hex::encode(&out[..]), OUTPUT);
+
+            let code = blake2b_decode_and_compress(input.as_ptr(), 212, out.as_mut_ptr());
+            assert_eq!(code, BLAKE2B_ERR_INPUT_LENGTH);
+
+            input[212] = 2;
+            let code = blake2b_decode_and_compress(input.as_ptr(), input.len(), out.as_mut_ptr());
+            assert_eq!(code, BLAKE2B_ERR_FINAL_BLOCK_FLAG);
+
+            let code = blake2b_decode_and_compress(std::ptr::null(), 213, out.as_mut_ptr());
+            assert_eq!(code, BLAKE2B_ERR_NULL_POINTER);
+        }
+    }
+
+    #[test]
+    fn test_capi_hasher() {
+        let mut out = [0u8; 64];
+
+        unsafe {
+            let hasher = blake2b_hasher_new(64, std::ptr::null(), 0);
+            assert!(!hasher.is_null());
+            assert_eq!(blake2b_hasher_update(hasher, b"ab".as_ptr(), 2), BLAKE2B_OK);
+            assert_eq!(blake2b_hasher_update(hasher, b"c".as_ptr(), 1), BLAKE2B_OK);
+            assert_eq!(
+                blake2b_hasher_finalize(hasher, out.as_mut_ptr(), out.len()),
+                BLAKE2B_OK
+            );
+            assert_eq!(hex::encode(&out[..]), OUTPUT);
+
+            let hasher = blake2b_hasher_new(32, std::ptr::null(), 0);
+            assert_eq!(
+                blake2b_hasher_finalize(hasher, out.as_mut_ptr(), out.len()),
+                BLAKE2B_ERR_OUTPUT_LENGTH
+            );
+
+            assert!(blake2b_hasher_new(65, std::ptr::null(), 0).is_null());
+            assert!(blake2b_hasher_new(64, std::ptr::null(), 1).is_null());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Hashing whole files through a memory map.
+
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+
+use memmap2::Mmap;
+
+use crate::hasher::{self, Params};
+use crate::parallel;
+
+/// How `hash_file` hashes a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Sequential blake2b, giving the same digest as streaming the file through the hasher.
+    Blake2b,
+    /// BLAKE2bp, with its four leaves hashed in parallel.
+    Blake2bp,
+    /// A two level BLAKE2b tree with leaves of `parallel::TREE_LEAF_BYTES` bytes, hashed in
+    /// parallel on every available core.
+    Tree,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "blake2b" => Ok(Mode::Blake2b),
+            "blake2bp" => Ok(Mode::Blake2bp),
+            "tree" => Ok(Mode::Tree),
+            _ => Err(format!(
+                "mode must be one of 'blake2b', 'blake2bp' or 'tree', got: '{}'",
+                name
+            )),
+        }
+    }
+}
+
+/// Calculate the digest of the file at `path` in the given `mode`, using the digest length, key,
+/// salt and personalization string given in `params`.
+///
+/// The file is memory mapped rather than read, so its contents must not change while it's being
+/// hashed.
+pub fn hash_file<P: AsRef<Path>>(path: P, mode: Mode, params: &Params) -> Result<Vec<u8>, String> {
+    let path = path.as_ref();
+    let describe = |err: std::io::Error| format!("{}: {}", path.display(), err);
+
+    let file = File::open(path).map_err(describe)?;
+    // Mapping an empty file fails on some platforms
+    let map = if file.metadata().map_err(describe)?.len() == 0 {
+        None
+    } else {
+        Some(unsafe { Mmap::map(&file) }.map_err(describe)?)
+    };
+    let input = map.as_deref().unwrap_or(&[]);
+
+    match mode {
+        Mode::Blake2b => hasher::hash(params, input),
+        Mode::Blake2bp => parallel::blake2bp(params, input),
+        Mode::Tree => {
+            let threads = thread::available_parallelism().map_or(1, |n| n.get());
+            parallel::tree(params, input, parallel::TREE_LEAF_BYTES, threads)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+
+    fn temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("blake2b-file-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let contents: Vec<u8> = (0..3 * parallel::TREE_LEAF_BYTES / 2)
+            .map(|i| i as u8)
+            .collect();
+        let path = temp_file("contents", &contents);
+        let params = Params::default();
+
+        let mut state = hasher::Blake2b::with_params(&params).unwrap();
+        for chunk in contents.chunks(4096) {
+            state.update(chunk);
+        }
+        assert_eq!(
+            hash_file(&path, Mode::Blake2b, &params).unwrap(),
+            state.finalize()
+        );
+        assert_eq!(
+            hash_file(&path, Mode::Blake2bp, &params).unwrap(),
+            parallel::blake2bp(&params, &contents).unwrap()
+        );
+        assert_eq!(
+            hash_file(&path, Mode::Tree, &params).unwrap(),
+            parallel::tree(&params, &contents, parallel::TREE_LEAF_BYTES, 1).unwrap()
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_empty_file() {
+        let path = temp_file("empty", b"");
+        let params = Params::default();
+
+        assert_eq!(
+            hash_file(&path, Mode::Blake2b, &params).unwrap(),
+            hasher::hash(&params, b"").unwrap()
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_file_error() {
+        assert!("blake2s".parse::<Mode>().is_err());
+        assert!(hash_file("/nonexistent/file", Mode::Blake2b, &Params::default()).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! A compatibility layer mirroring libsodium's `crypto_generichash` API.
+//!
+//! See here: https://doc.libsodium.org/hashing/generic_hashing
+
+use crate::hasher::{self, Blake2b, Midstate, Params};
+
+pub const BYTES: usize = 32;
+pub const BYTES_MIN: usize = 16;
+pub const BYTES_MAX: usize = 64;
+pub const KEYBYTES: usize = 32;
+pub const KEYBYTES_MIN: usize = 16;
+pub const KEYBYTES_MAX: usize = 64;
+pub const SALTBYTES: usize = hasher::SALTBYTES;
+pub const PERSONALBYTES: usize = hasher::PERSONALBYTES;
+
+/// The multi-part hashing state used by `crypto_generichash_init`, `crypto_generichash_update`
+/// and `crypto_generichash_final`.
+#[derive(Clone)]
+pub struct State(Blake2b);
+
+impl State {
+    /// Capture the midstate of the hashing state, see `Blake2b::midstate`.
+    pub fn midstate(&self) -> Midstate {
+        self.0.midstate()
+    }
+
+    /// Create a hashing state with the same parameters that resumes hashing from `midstate`.
+    pub fn resume(&self, midstate: &Midstate) -> Self {
+        State(self.0.resume(midstate))
+    }
+
+    /// Serialize the hashing state in the format described by `Blake2b::to_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Restore a hashing state serialized by `to_bytes`.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, String> {
+        let state = Blake2b::from_bytes(input)?;
+        if state.digest_length() < BYTES_MIN {
+            return Err(format!(
+                "output length must be between {} and {} bytes, got: {}",
+                BYTES_MIN,
+                BYTES_MAX,
+                state.digest_length(),
+            ));
+        }
+
+        Ok(State(state))
+    }
+}
+
+fn checked_params(outlen: usize, key: &[u8]) -> Result<Params, String> {
+    if !(BYTES_MIN..=BYTES_MAX).contains(&outlen) {
+        return Err(format!(
+            "output length must be between {} and {} bytes, got: {}",
+            BYTES_MIN, BYTES_MAX, outlen,
+        ));
+    }
+    if !key.is_empty() && !(KEYBYTES_MIN..=KEYBYTES_MAX).contains(&key.len()) {
+        return Err(format!(
+            "key length must be 0 or between {} and {} bytes, got: {}",
+            KEYBYTES_MIN,
+            KEYBYTES_MAX,
+            key.len(),
+        ));
+    }
+
+    Ok(Params {
+        digest_length: outlen,
+        key: key.to_vec(),
+        ..Params::default()
+    })
+}
+
+/// Calculate an `outlen` byte digest of `message`, optionally keyed with `key`.
+pub fn crypto_generichash(outlen: usize, message: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+    hasher::hash(&checked_params(outlen, key)?, message)
+}
+
+/// Calculate an `outlen` byte digest of `message`, optionally keyed with `key`, using the given
+/// `salt` and `personal` parameters.  Passing `None` for either is equivalent to passing all zeros,
+/// as with a NULL pointer in libsodium.
+pub fn crypto_generichash_blake2b_salt_personal(
+    outlen: usize,
+    message: &[u8],
+    key: &[u8],
+    salt: Option<&[u8; SALTBYTES]>,
+    personal: Option<&[u8; PERSONALBYTES]>,
+) -> Result<Vec<u8>, String> {
+    let mut params = checked_params(outlen, key)?;
+    if let Some(salt) = salt {
+        params.salt = *salt;
+    }
+    if let Some(personal) = personal {
+        params.personal = *personal;
+    }
+
+    hasher::hash(&params, message)
+}
+
+/// Create a multi-part hashing state for an `outlen` byte digest, optionally keyed with `key`.
+pub fn crypto_generichash_init(key: &[u8], outlen: usize) -> Result<State, String> {
+    Ok(State(Blake2b::with_params(&checked_params(outlen, key)?)?))
+}
+
+/// Absorb the bytes in `message` into the multi-part hashing state `state`.
+pub fn crypto_generichash_update(state: &mut State, message: &[u8]) {
+    state.0.update(message);
+}
+
+/// Consume the multi-part hashing state `state` and return its digest.
+pub fn crypto_generichash_final(state: State) -> Vec<u8> {
+    state.0.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vectors from libsodium's `test/default/generichash.exp` where the input is the bytes
+    /// `0..i`, the key is the bytes `0..i + 1` and the output length is `i + 1`.  Only the
+    /// vectors within the documented output and key length bounds are included.
+    const KEYED_EXAMPLES: &[(usize, &str)] = &[
+        (15, "0c7c9c3922d41a7b2b3b20f92685d560"),
+        (16, "8508c01d19709bdd881866aa1f8c63ca06"),
+        (31, "a9f51bb7f6a3e9cdb96ce652c07d177962a348a9cced1b92f948187e59b44463"),
+        (
+            47,
+            "95d5cd54c6722ac4335fa0ab38d388c9fd0baea48a9078605e400534ef38f13abb1d770da84b90b0256e1c1b64f54ba9",
+        ),
+        (
+            63,
+            "bd965bf31e87d70327536f2a341cebc4768eca275fa05ef98f7f1b71a0351298de006fba73fe6733ed01d75801b4a928e54231b38e38c562b2e33ea1284992fa",
+        ),
+    ];
+
+    /// Vectors from libsodium's `test/default/generichash3.exp`, laid out as above with a fixed
+    /// salt and personalization string.
+    const SALT_PERSONAL_EXAMPLES: &[(usize, &str)] = &[
+        (15, "5fbe885c4b2d4e0d78dc5905622a277a"),
+        (31, "9b273ebe335540b87be899abe169389ed61ed262c3a0a16e4998bbf752f0bee3"),
+        (
+            47,
+            "7eed787abaa7f4e8b8aa3090f0676201cfbaaf350899661cdd5216ac0b5cd874443f5c0688ffd7ca1ccbfe1ca7e1a3f5",
+        ),
+        (
+            63,
+            "5fcdcc02be7714a0dbc77df498bf999ea9225d564adca1c121c9af03af92cac8177b9b4a86bcc47c79aa32aac58a3fef967b2132e9352d4613fe890beed2571b",
+        ),
+    ];
+
+    const SALT: &[u8; SALTBYTES] = b"5b6b41ed9b343fe0";
+    const PERSONAL: &[u8; PERSONALBYTES] = b"5126fb2a37400d2a";
+
+    fn sequence(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn test_generichash_keyed() {
+        for (i, expected) in KEYED_EXAMPLES {
+            let result_bytes = crypto_generichash(i + 1, &sequence(*i), &sequence(i + 1)).unwrap();
+
+            assert_eq!(hex::encode(result_bytes), *expected);
+        }
+    }
+
+    #[test]
+    fn test_generichash_multipart() {
+        for (i, expected) in KEYED_EXAMPLES {
+            let message = sequence(*i);
+
+            let mut state = crypto_generichash_init(&sequence(i + 1), i + 1).unwrap();
+            for chunk in message.chunks(7) {
+                crypto_generichash_update(&mut state, chunk);
+            }
+
+            assert_eq!(hex::encode(crypto_generichash_final(state)), *expected);
+        }
+    }
+
+    #[test]
+    fn test_generichash_salt_personal() {
+        for (i, expected) in SALT_PERSONAL_EXAMPLES {
+            let result_bytes = crypto_generichash_blake2b_salt_personal(
+                i + 1,
+                &sequence(*i),
+                &sequence(i + 1),
+                Some(SALT),
+                Some(PERSONAL),
+            )
+            .unwrap();
+
+            assert_eq!(hex::encode(result_bytes), *expected);
+        }
+    }
+
+    #[test]
+    fn test_generichash_error() {
+        assert!(crypto_generichash(BYTES_MIN - 1, b"", b"").is_err());
+        assert!(crypto_generichash(BYTES_MAX + 1, b"", b"").is_err());
+        assert!(crypto_generichash(BYTES, b"", &sequence(KEYBYTES_MIN - 1)).is_err());
+        assert!(crypto_generichash(BYTES, b"", &sequence(KEYBYTES_MAX + 1)).is_err());
+        assert!(crypto_generichash_init(&sequence(KEYBYTES_MAX + 1), BYTES).is_err());
+    }
+}
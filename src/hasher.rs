@@ -0,0 +1,631 @@
+use std::convert::TryInto;
+
+use crate::blake2b;
+use crate::zeroize::zeroize;
+
+/// Number of bytes in a single blake2b message block.
+pub const BLOCKBYTES: usize = 128;
+/// Maximum number of bytes in a blake2b digest.
+pub const OUTBYTES: usize = 64;
+/// Maximum number of bytes in a blake2b key.
+pub const KEYBYTES: usize = 64;
+/// Number of bytes in a blake2b salt.
+pub const SALTBYTES: usize = 16;
+/// Number of bytes in a blake2b personalization string.
+pub const PERSONALBYTES: usize = 16;
+
+/// Number of rounds performed by the compression function in standard blake2b.
+const ROUNDS: usize = 12;
+
+/// Leading bytes of a serialized hashing state.
+const STATE_MAGIC: &[u8; 4] = b"B2BS";
+/// Version of the serialized hashing state format written by `Blake2b::to_bytes`.
+pub const STATE_VERSION: u8 = 1;
+/// Number of bytes in a serialized hashing state, not counting the buffered partial block.
+const STATE_HEADER_BYTES: usize = 4 + 1 + PARAMBYTES + 1 + 8 * 8 + 2 * 8 + 1;
+/// Number of bytes in a blake2b parameter block.
+const PARAMBYTES: usize = 64;
+
+/// Parameters used to initialize a blake2b hashing state.
+///
+/// The tree parameters default to those of sequential hashing: a fanout and maximum depth of 1
+/// with the rest zero.
+///
+/// See here: https://blake2.net/blake2.pdf (sections 2.5 and 2.10)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Params {
+    pub digest_length: usize,
+    pub key: Vec<u8>,
+    pub salt: [u8; SALTBYTES],
+    pub personal: [u8; PERSONALBYTES],
+    pub fanout: u8,
+    pub max_depth: u8,
+    pub leaf_length: u32,
+    pub node_offset: u64,
+    pub node_depth: u8,
+    pub inner_length: u8,
+    /// Whether this is the last node at its depth in a hash tree, which sets the last node flag
+    /// when compressing the final block.
+    pub last_node: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            digest_length: OUTBYTES,
+            key: Vec::new(),
+            salt: [0; SALTBYTES],
+            personal: [0; PERSONALBYTES],
+            fanout: 1,
+            max_depth: 1,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: 0,
+            last_node: false,
+        }
+    }
+}
+
+impl Drop for Params {
+    fn drop(&mut self) {
+        zeroize(&mut self.key);
+    }
+}
+
+impl Params {
+    fn validate(&self) -> Result<(), String> {
+        if self.digest_length == 0 || self.digest_length > OUTBYTES {
+            return Err(format!(
+                "digest length must be between 1 and {} bytes, got: {}",
+                OUTBYTES, self.digest_length,
+            ));
+        }
+        if self.key.len() > KEYBYTES {
+            return Err(format!(
+                "key length must be at most {} bytes, got: {}",
+                KEYBYTES,
+                self.key.len(),
+            ));
+        }
+        if self.inner_length as usize > OUTBYTES {
+            return Err(format!(
+                "inner length must be at most {} bytes, got: {}",
+                OUTBYTES, self.inner_length,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Encode the parameter block.  The key itself isn't part of it, only its length.
+    fn to_block(&self) -> [u8; PARAMBYTES] {
+        let mut block = [0u8; PARAMBYTES];
+
+        block[0] = self.digest_length as u8;
+        block[1] = self.key.len() as u8;
+        block[2] = self.fanout;
+        block[3] = self.max_depth;
+        block[4..8].copy_from_slice(&self.leaf_length.to_le_bytes());
+        block[8..16].copy_from_slice(&self.node_offset.to_le_bytes());
+        block[16] = self.node_depth;
+        block[17] = self.inner_length;
+        block[32..48].copy_from_slice(&self.salt);
+        block[48..64].copy_from_slice(&self.personal);
+
+        block
+    }
+}
+
+/// Compute the initial state vector `h` by mixing the parameter block into the blake2b IV.
+fn initial_state(param_block: &[u8; PARAMBYTES]) -> [u64; 8] {
+    let mut h = blake2b::eight_words(param_block);
+    for (word, iv) in h.iter_mut().zip(blake2b::IV.iter()) {
+        *word ^= iv;
+    }
+
+    h
+}
+
+/// The midstate of a streaming hashing state: the chaining value and offset counters after every
+/// block compressed so far, which are the `starting_state` and `offset_counters` inputs to the
+/// next call of the compression function F, together with the input buffered since then.
+///
+/// Messages sharing a long prefix can be hashed by absorbing the prefix once, taking its midstate
+/// and resuming a copy of the hashing state from it for each suffix with `Blake2b::resume`.
+#[derive(Clone)]
+pub struct Midstate {
+    h: [u64; 8],
+    t: [u64; 2],
+    buf: [u8; BLOCKBYTES],
+    buf_len: usize,
+}
+
+impl Midstate {
+    /// Create a midstate from a starting state, offset counters and up to one block of input not
+    /// yet compressed, for example as computed by calling `blake2b::F` directly.
+    pub fn new(
+        starting_state: &[u64],
+        offset_counters: &[u64],
+        buffered: &[u8],
+    ) -> Result<Self, String> {
+        blake2b::check_lengths(starting_state, &[0; 16], offset_counters)?;
+        if buffered.len() > BLOCKBYTES {
+            return Err(format!(
+                "buffered input must be at most {} bytes, got: {}",
+                BLOCKBYTES,
+                buffered.len(),
+            ));
+        }
+
+        let mut midstate = Midstate {
+            h: starting_state.try_into().unwrap(),
+            t: offset_counters.try_into().unwrap(),
+            buf: [0; BLOCKBYTES],
+            buf_len: buffered.len(),
+        };
+        midstate.buf[..buffered.len()].copy_from_slice(buffered);
+
+        Ok(midstate)
+    }
+
+    /// The chaining value `h`, to be passed as the starting state of the next call to F.
+    pub fn starting_state(&self) -> [u64; 8] {
+        self.h
+    }
+
+    /// The 128-bit count `t` of bytes compressed so far, to be passed as the offset counters of
+    /// the next call to F.
+    pub fn offset_counters(&self) -> [u64; 2] {
+        self.t
+    }
+
+    /// The input absorbed since the last compressed block.  A full block is only compressed once
+    /// more input follows it, so this holds between 0 and 128 bytes.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[..self.buf_len]
+    }
+}
+
+impl Drop for Midstate {
+    fn drop(&mut self) {
+        zeroize(&mut self.h);
+        zeroize(&mut self.t);
+        zeroize(&mut self.buf);
+    }
+}
+
+/// A streaming blake2b hashing state built on top of the compression function F.
+///
+/// See here: https://tools.ietf.org/html/rfc7693#section-3.3
+#[derive(Clone)]
+pub struct Blake2b {
+    h: [u64; 8],
+    t: [u64; 2],
+    buf: [u8; BLOCKBYTES],
+    buf_len: usize,
+    digest_length: usize,
+    param_block: [u8; PARAMBYTES],
+    last_node: bool,
+}
+
+impl Blake2b {
+    /// Create an unkeyed hashing state that produces digests of `digest_length` bytes.
+    pub fn new(digest_length: usize) -> Result<Self, String> {
+        let mut params = Params::default();
+        params.digest_length = digest_length;
+
+        Self::with_params(&params)
+    }
+
+    /// Create a hashing state from the parameters given in `params`.
+    pub fn with_params(params: &Params) -> Result<Self, String> {
+        let mut state = Self::root_with_params(params)?;
+
+        // A keyed hash processes the key, zero padded to a full block, as the first block of the
+        // message.
+        if !params.key.is_empty() {
+            state.buf[..params.key.len()].copy_from_slice(&params.key);
+            state.buf_len = BLOCKBYTES;
+        }
+
+        Ok(state)
+    }
+
+    /// Create a hashing state from the parameters given in `params` without absorbing the key.
+    /// Its length still goes into the parameter block.  The root node of a keyed hash tree is
+    /// initialized this way, since the key has already been absorbed by the leaves.
+    pub(crate) fn root_with_params(params: &Params) -> Result<Self, String> {
+        params.validate()?;
+
+        let param_block = params.to_block();
+        let state = Blake2b {
+            h: initial_state(&param_block),
+            t: [0, 0],
+            buf: [0; BLOCKBYTES],
+            buf_len: 0,
+            digest_length: params.digest_length,
+            param_block,
+            last_node: params.last_node,
+        };
+
+        Ok(state)
+    }
+
+    /// Absorb the bytes in `input` into the hashing state.
+    pub fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // The last block must be compressed with the final block flag set, so a full buffer
+            // is only compressed once we know more input follows it.
+            if self.buf_len == BLOCKBYTES {
+                self.increment_counter(BLOCKBYTES);
+                self.compress(false);
+                self.buf_len = 0;
+            }
+
+            let take = std::cmp::min(BLOCKBYTES - self.buf_len, input.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&input[..take]);
+            self.buf_len += take;
+            input = &input[take..];
+        }
+    }
+
+    /// Compute the digest of all input absorbed so far.  The state itself is left unchanged so
+    /// that more input may be absorbed afterwards.
+    pub fn finalize(&self) -> Vec<u8> {
+        let mut out = self.finalize_full();
+        zeroize(&mut out[self.digest_length..]);
+        out.truncate(self.digest_length);
+
+        out
+    }
+
+    /// Compute all `OUTBYTES` bytes of output, whatever the digest length in the parameter
+    /// block.  The leaves of BLAKE2bp and tree hashes pass their full output on to the root even
+    /// though their parameter block holds the digest length of the root.
+    pub(crate) fn finalize_full(&self) -> Vec<u8> {
+        let mut state = self.clone();
+
+        state.increment_counter(state.buf_len);
+        for x in state.buf[state.buf_len..].iter_mut() {
+            *x = 0;
+        }
+        state.compress(true);
+
+        let mut out = Vec::with_capacity(OUTBYTES);
+        for word in state.h.iter() {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+
+        out
+    }
+
+    /// Capture the midstate of this hashing state.
+    pub fn midstate(&self) -> Midstate {
+        Midstate {
+            h: self.h,
+            t: self.t,
+            buf: self.buf,
+            buf_len: self.buf_len,
+        }
+    }
+
+    /// Create a hashing state with the same parameters as this one that resumes hashing from
+    /// `midstate`.  Only the midstate is copied, so this is as cheap as cloning.
+    pub fn resume(&self, midstate: &Midstate) -> Self {
+        Blake2b {
+            h: midstate.h,
+            t: midstate.t,
+            buf: midstate.buf,
+            buf_len: midstate.buf_len,
+            digest_length: self.digest_length,
+            param_block: self.param_block,
+            last_node: self.last_node,
+        }
+    }
+
+    /// The number of bytes in the digests produced by this hashing state.
+    pub fn digest_length(&self) -> usize {
+        self.digest_length
+    }
+
+    /// Serialize the hashing state so that it can be restored with `from_bytes`, possibly on
+    /// another machine, and carry on producing the same digests.
+    ///
+    /// The encoding starts with the magic bytes `B2BS` and a version byte, currently 1, followed
+    /// by the 64 byte parameter block, a byte holding 1 for the last node of a hash tree level and
+    /// 0 otherwise, the 8 words of `h` and the 2 words of the 128-bit counter `t` as little endian
+    /// integers, the length of the buffered partial block as a single byte and finally the
+    /// buffered bytes.
+    ///
+    /// The key itself isn't included, but until more than a block of input has been absorbed
+    /// after it, a keyed state's buffer still holds the key, so treat the result as secret.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(STATE_HEADER_BYTES + self.buf_len);
+
+        out.extend_from_slice(STATE_MAGIC);
+        out.push(STATE_VERSION);
+        out.extend_from_slice(&self.param_block);
+        out.push(self.last_node as u8);
+        for word in self.h.iter().chain(self.t.iter()) {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.push(self.buf_len as u8);
+        out.extend_from_slice(&self.buf[..self.buf_len]);
+
+        out
+    }
+
+    /// Restore a hashing state serialized by `to_bytes`.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, String> {
+        if input.len() < 5 || &input[..4] != STATE_MAGIC {
+            return Err("input is not a serialized blake2b hashing state".into());
+        }
+
+        if input[4] != STATE_VERSION {
+            return Err(format!(
+                "unsupported hashing state version, got: {}",
+                input[4]
+            ));
+        }
+        if input.len() < STATE_HEADER_BYTES {
+            return Err("input is not a serialized blake2b hashing state".into());
+        }
+
+        let mut param_block = [0u8; PARAMBYTES];
+        param_block.copy_from_slice(&input[5..5 + PARAMBYTES]);
+        let last_node = input[5 + PARAMBYTES] != 0;
+        let header_bytes = STATE_HEADER_BYTES;
+
+        let digest_length = param_block[0] as usize;
+        if digest_length == 0 || digest_length > OUTBYTES {
+            return Err(format!(
+                "digest length must be between 1 and {} bytes, got: {}",
+                OUTBYTES, digest_length,
+            ));
+        }
+        if param_block[1] as usize > KEYBYTES {
+            return Err(format!(
+                "key length must be at most {} bytes, got: {}",
+                KEYBYTES, param_block[1],
+            ));
+        }
+
+        let buf_len = input[header_bytes - 1] as usize;
+        if buf_len > BLOCKBYTES || input.len() != header_bytes + buf_len {
+            return Err(format!(
+                "serialized hashing state with {} buffered bytes should be exactly {} bytes, got: {}",
+                buf_len,
+                header_bytes + buf_len,
+                input.len(),
+            ));
+        }
+
+        let words = &input[header_bytes - 81..header_bytes - 1];
+        let mut state = Blake2b {
+            h: blake2b::eight_words(&words[..64]),
+            t: [
+                u64::from_le_bytes(words[64..72].try_into().unwrap()),
+                u64::from_le_bytes(words[72..80].try_into().unwrap()),
+            ],
+            buf: [0; BLOCKBYTES],
+            buf_len,
+            digest_length,
+            param_block,
+            last_node,
+        };
+        state.buf[..buf_len].copy_from_slice(&input[header_bytes..]);
+
+        Ok(state)
+    }
+
+    fn increment_counter(&mut self, n: usize) {
+        let (t0, carry) = self.t[0].overflowing_add(n as u64);
+        self.t[0] = t0;
+        self.t[1] = self.t[1].wrapping_add(carry as u64);
+    }
+
+    fn compress(&mut self, final_block_flag: bool) {
+        let mut block = blake2b::sixteen_words(&self.buf);
+        let result = if final_block_flag && self.last_node {
+            blake2b::F_last_node(ROUNDS, &self.h, &block, &self.t, true)
+        } else {
+            blake2b::F(ROUNDS, &self.h, &block, &self.t, final_block_flag)
+        };
+        self.h = blake2b::eight_words(&result);
+
+        zeroize(&mut block);
+    }
+}
+
+impl Drop for Blake2b {
+    fn drop(&mut self) {
+        zeroize(&mut self.h);
+        zeroize(&mut self.t);
+        zeroize(&mut self.buf);
+        self.buf_len = 0;
+    }
+}
+
+/// Calculate the blake2b digest of `input` using the parameters given in `params`.
+pub fn hash(params: &Params, input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut state = Blake2b::with_params(params)?;
+    state.update(input);
+    Ok(state.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vectors from RFC 7693 and the blake2b reference implementation's known answer tests
+    const UNKEYED_EXAMPLES: &[(&str, &str)] = &[
+        (
+            "",
+            "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+        ),
+        (
+            "616263",
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+    ];
+
+    #[test]
+    fn test_hasher_unkeyed() {
+        for (inp, expected) in UNKEYED_EXAMPLES {
+            let input_bytes = hex::decode(inp).unwrap();
+            let result_bytes = hash(&Params::default(), &input_bytes).unwrap();
+
+            assert_eq!(hex::encode(result_bytes), *expected);
+        }
+    }
+
+    #[test]
+    fn test_hasher_incremental_matches_oneshot() {
+        let input_bytes: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let params = Params {
+            digest_length: 40,
+            key: (0..64).collect(),
+            ..Params::default()
+        };
+        let expected = hash(&params, &input_bytes).unwrap();
+
+        for chunk_size in &[1, 63, 127, 128, 129, 500] {
+            let mut state = Blake2b::with_params(&params).unwrap();
+            for chunk in input_bytes.chunks(*chunk_size) {
+                state.update(chunk);
+            }
+
+            assert_eq!(state.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hasher_midstate_prefix() {
+        let prefix: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let mut state = Blake2b::new(64).unwrap();
+        state.update(&prefix);
+        let midstate = state.midstate();
+
+        assert_eq!(midstate.offset_counters(), [256, 0]);
+        assert_eq!(midstate.buffered(), &prefix[256..]);
+
+        for suffix in &[&b""[..], b"abc", &[7; 1000]] {
+            let mut resumed = state.resume(&midstate);
+            resumed.update(suffix);
+
+            let expected = hash(&Params::default(), &[&prefix[..], suffix].concat()).unwrap();
+            assert_eq!(resumed.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hasher_midstate_from_f() {
+        // Compress the first block of the message by hand and resume hashing after it
+        let message: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let state = Blake2b::new(64).unwrap();
+        let h = state.midstate().starting_state();
+        let m = blake2b::sixteen_words(&message[..128]);
+        let h = blake2b::eight_words(&blake2b::F(ROUNDS, &h, &m, &[128, 0], false));
+
+        let midstate = Midstate::new(&h, &[128, 0], &message[128..]).unwrap();
+        let resumed = state.resume(&midstate);
+        assert_eq!(
+            resumed.finalize(),
+            hash(&Params::default(), &message).unwrap(),
+        );
+
+        assert!(Midstate::new(&h[..7], &[128, 0], b"").is_err());
+        assert!(Midstate::new(&h, &[128], b"").is_err());
+        assert!(Midstate::new(&h, &[128, 0], &[0; 129]).is_err());
+    }
+
+    #[test]
+    fn test_hasher_serialization_round_trip() {
+        let input_bytes: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let params = Params {
+            digest_length: 40,
+            key: (0..64).collect(),
+            salt: *b"5b6b41ed9b343fe0",
+            personal: *b"5126fb2a37400d2a",
+            ..Params::default()
+        };
+        let expected = hash(&params, &input_bytes).unwrap();
+
+        for split in &[0, 1, 127, 128, 129, 256, 999, 1000] {
+            let mut state = Blake2b::with_params(&params).unwrap();
+            state.update(&input_bytes[..*split]);
+
+            let serialized = state.to_bytes();
+            assert_eq!(serialized[..5], *b"B2BS\x01");
+            let mut restored = Blake2b::from_bytes(&serialized).unwrap();
+            assert_eq!(restored.to_bytes(), serialized);
+
+            restored.update(&input_bytes[*split..]);
+            assert_eq!(restored.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hasher_serialization_layout() {
+        let mut state = Blake2b::new(64).unwrap();
+        state.update(b"abc");
+        let serialized = state.to_bytes();
+
+        assert_eq!(serialized.len(), STATE_HEADER_BYTES + 3);
+        assert_eq!(serialized[5..9], [64, 0, 1, 1]);
+        assert_eq!(serialized[9..69], [0; 60]);
+        assert_eq!(serialized[69], 0);
+        assert_eq!(serialized[70..78], 0x6a09e667f2bdc948u64.to_le_bytes());
+        assert_eq!(serialized[134..150], [0; 16]);
+        assert_eq!(serialized[150..], [3, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn test_hasher_deserialization_error() {
+        let serialized = Blake2b::new(64).unwrap().to_bytes();
+
+        let mut bad_inputs = vec![
+            Vec::new(),
+            serialized[..serialized.len() - 1].to_vec(),
+            [&serialized[..], &[0]].concat(),
+        ];
+        for (offset, value) in &[(0, b'X'), (4, 2), (5, 0), (5, 65), (6, 65), (150, 129)] {
+            let mut input = serialized.clone();
+            input[*offset] = *value;
+            bad_inputs.push(input);
+        }
+
+        for input in bad_inputs {
+            if Blake2b::from_bytes(&input).is_ok() {
+                panic!("expected Result::Err but got Result::Ok");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hasher_params_error() {
+        let bad_params = &[
+            Params {
+                digest_length: 0,
+                key: Vec::new(),
+                ..Params::default()
+            },
+            Params {
+                digest_length: 65,
+                key: Vec::new(),
+                ..Params::default()
+            },
+            Params {
+                key: vec![0; 65],
+                ..Params::default()
+            },
+        ];
+
+        for params in bad_params {
+            if Blake2b::with_params(params).is_ok() {
+                panic!("expected Result::Err but got Result::Ok");
+            }
+        }
+    }
+}
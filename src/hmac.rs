@@ -0,0 +1,159 @@
+//! The HMAC construction over blake2b-512.
+//!
+//! See here: https://tools.ietf.org/html/rfc2104
+
+use crate::hasher::{self, Blake2b, Params};
+use crate::verify;
+use crate::zeroize::zeroize;
+
+/// Number of bytes in a block of input to the HMAC construction.
+pub const BLOCKBYTES: usize = hasher::BLOCKBYTES;
+/// Number of bytes in an HMAC-BLAKE2b-512 tag.
+pub const OUTBYTES: usize = hasher::OUTBYTES;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// A streaming HMAC-BLAKE2b-512 state.
+#[derive(Clone)]
+pub struct Hmac {
+    inner: Blake2b,
+    outer: Blake2b,
+}
+
+impl Hmac {
+    /// Create an HMAC state keyed with `key`.  Keys longer than the blake2b block size are hashed
+    /// first, as described in RFC 2104.
+    pub fn new(key: &[u8]) -> Self {
+        let mut padded_key = [0u8; BLOCKBYTES];
+        if key.len() > BLOCKBYTES {
+            let mut hashed_key = hasher::hash(&Params::default(), key).unwrap();
+            padded_key[..hashed_key.len()].copy_from_slice(&hashed_key);
+            zeroize(&mut hashed_key);
+        } else {
+            padded_key[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = Blake2b::new(OUTBYTES).unwrap();
+        let mut outer = inner.clone();
+
+        let mut pad = [0u8; BLOCKBYTES];
+        for (x, k) in pad.iter_mut().zip(padded_key.iter()) {
+            *x = k ^ IPAD;
+        }
+        inner.update(&pad);
+        for (x, k) in pad.iter_mut().zip(padded_key.iter()) {
+            *x = k ^ OPAD;
+        }
+        outer.update(&pad);
+
+        zeroize(&mut padded_key);
+        zeroize(&mut pad);
+
+        Hmac { inner, outer }
+    }
+
+    /// Absorb the bytes in `input` into the HMAC state.
+    pub fn update(&mut self, input: &[u8]) {
+        self.inner.update(input);
+    }
+
+    /// Compute the tag of all input absorbed so far.  The state itself is left unchanged so that
+    /// more input may be absorbed afterwards.
+    pub fn finalize(&self) -> Vec<u8> {
+        let mut inner_digest = self.inner.finalize();
+        let mut outer = self.outer.clone();
+        outer.update(&inner_digest);
+        zeroize(&mut inner_digest);
+
+        outer.finalize()
+    }
+
+    /// Check in constant time whether `tag` is the tag of all input absorbed so far.
+    pub fn verify(&self, tag: &[u8]) -> bool {
+        verify::compare_digest(&self.finalize(), tag)
+    }
+}
+
+/// Calculate the HMAC-BLAKE2b-512 tag of `input` keyed with `key`.
+pub fn hmac(key: &[u8], input: &[u8]) -> Vec<u8> {
+    let mut state = Hmac::new(key);
+    state.update(input);
+    state.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test cases 1-4, 6 and 7 from RFC 4231 section 4 with outputs for HMAC-BLAKE2b-512
+    const EXAMPLES: &[(&str, &str, &str)] = &[
+        (
+            "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            "4869205468657265",
+            "358a6a184924894fc34bee5680eedf57d84a37bb38832f288e3b27dc63a98cc8c91e76da476b508bc6b2d408a248857452906e4a20b48c6b4b55d2df0fe1dd24",
+        ),
+        (
+            "4a656665",
+            "7768617420646f2079612077616e7420666f72206e6f7468696e673f",
+            "6ff884f8ddc2a6586b3c98a4cd6ebdf14ec10204b6710073eb5865ade37a2643b8807c1335d107ecdb9ffeaeb6828c4625ba172c66379efcd222c2de11727ab4",
+        ),
+        (
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd",
+            "f43bc62c7a99353c3b2c60e8ef24fbbd42e9547866dc9c5be4edc6f4a7d4bc0ac620c2c60034d040f0dbaf86f9e9cd7891a095595eed55e2a996215f0c15c018",
+        ),
+        (
+            "0102030405060708090a0b0c0d0e0f10111213141516171819",
+            "cdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd",
+            "e5dbb6de2fee42a1caa06e4e7b84ce408ffa5c4a9de2632eca769cde8875014c72d0720feaf53f76e6a180357f528d7bf484fa3a14e8cc1f0f3bada717b43491",
+        ),
+        (
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "54657374205573696e67204c6172676572205468616e20426c6f636b2d53697a65204b6579202d2048617368204b6579204669727374",
+            "a54b2943b2a20227d41ca46c0945af09bc1faefb2f49894c23aebc557fb79c4889dca74408dc865086667aedee4a3185c53a49c80b814c4c5813ea0c8b38a8f8",
+        ),
+        (
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "5468697320697320612074657374207573696e672061206c6172676572207468616e20626c6f636b2d73697a65206b657920616e642061206c6172676572207468616e20626c6f636b2d73697a6520646174612e20546865206b6579206e6565647320746f20626520686173686564206265666f7265206265696e6720757365642062792074686520484d414320616c676f726974686d2e",
+            "ab347980a64b5e825dd10e7d32fd43a01a8e6dea267ab9ad7d913524526618925311afbcb0c49519cbebdd709540a8d725fb911ac2aee9b2a3aa43d796123393",
+        ),
+    ];
+
+    #[test]
+    fn test_hmac_vectors() {
+        for (key, data, expected) in EXAMPLES {
+            let key = hex::decode(key).unwrap();
+            let data = hex::decode(data).unwrap();
+
+            assert_eq!(hex::encode(hmac(&key, &data)), *expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_incremental_matches_oneshot() {
+        let key = b"key";
+        let input_bytes: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let expected = hmac(key, &input_bytes);
+
+        let mut state = Hmac::new(key);
+        for chunk in input_bytes.chunks(100) {
+            state.update(chunk);
+        }
+
+        assert_eq!(state.finalize(), expected);
+    }
+
+    #[test]
+    fn test_hmac_verify() {
+        let mut state = Hmac::new(b"key");
+        state.update(b"message");
+        let mut tag = state.finalize();
+
+        assert!(state.verify(&tag));
+        assert!(!state.verify(&tag[..32]));
+
+        tag[63] ^= 1;
+        assert!(!state.verify(&tag));
+    }
+}
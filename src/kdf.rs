@@ -0,0 +1,379 @@
+//! Key derivation functions built on blake2b: extract-and-expand KDFs and the password based
+//! PBKDF2.
+//!
+//! See here: https://tools.ietf.org/html/rfc5869 and https://tools.ietf.org/html/rfc8018
+
+use crate::hasher::{self, Blake2b, Params};
+use crate::hmac::{self, Hmac};
+use crate::zeroize::zeroize;
+
+/// Number of bytes in a pseudorandom key produced by the extract step.
+pub const PRKBYTES: usize = hasher::OUTBYTES;
+/// Maximum number of bytes of output keying material produced by the expand step.
+pub const MAX_OUTPUT_LENGTH: usize = 255 * PRKBYTES;
+
+fn keyed_state(key: &[u8]) -> Result<Blake2b, String> {
+    Blake2b::with_params(&Params {
+        key: key.to_vec(),
+        ..Params::default()
+    })
+}
+
+fn check_output_length(length: usize) -> Result<(), String> {
+    if length > MAX_OUTPUT_LENGTH {
+        return Err(format!(
+            "output length must be at most {} bytes, got: {}",
+            MAX_OUTPUT_LENGTH, length,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run the expand loop shared by both KDFs, where `prf` maps the previous output block, `info`
+/// and the block counter to the next output block.
+fn expand_with<P>(info: &[u8], length: usize, mut prf: P) -> Result<Vec<u8>, String>
+where
+    P: FnMut(&[&[u8]]) -> Result<Vec<u8>, String>,
+{
+    check_output_length(length)?;
+
+    let mut okm = Vec::with_capacity(length + PRKBYTES);
+    let mut t = Vec::new();
+    let mut counter = 1u8;
+
+    while okm.len() < length {
+        let next = prf(&[&t, info, &[counter]])?;
+        zeroize(&mut t);
+        t = next;
+
+        okm.extend_from_slice(&t);
+        counter = counter.wrapping_add(1);
+    }
+    zeroize(&mut t);
+    zeroize(&mut okm[length..]);
+    okm.truncate(length);
+
+    Ok(okm)
+}
+
+/// Extract a pseudorandom key from the input keying material `ikm` using blake2b's native keyed
+/// mode with `salt` as the key.  An empty salt is replaced by 64 zero bytes, and a salt longer
+/// than 64 bytes is first hashed down to 64 bytes.
+pub fn extract(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let mut key = if salt.is_empty() {
+        vec![0; hasher::KEYBYTES]
+    } else if salt.len() > hasher::KEYBYTES {
+        hasher::hash(&Params::default(), salt).unwrap()
+    } else {
+        salt.to_vec()
+    };
+
+    let mut state = keyed_state(&key).unwrap();
+    zeroize(&mut key);
+
+    state.update(ikm);
+    state.finalize()
+}
+
+/// Expand the pseudorandom key `prk` into `length` bytes of output keying material bound to
+/// `info` using blake2b's native keyed mode with `prk` as the key.
+pub fn expand(prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, String> {
+    let state = keyed_state(prk)?;
+
+    expand_with(info, length, |parts| {
+        let mut state = state.clone();
+        for part in parts {
+            state.update(part);
+        }
+        Ok(state.finalize())
+    })
+}
+
+/// Derive `length` bytes of output keying material from `ikm`, `salt` and `info` with the native
+/// keyed mode extract and expand steps.
+pub fn derive(ikm: &[u8], salt: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, String> {
+    let mut prk = extract(salt, ikm);
+    let okm = expand(&prk, info, length);
+    zeroize(&mut prk);
+
+    okm
+}
+
+/// Extract a pseudorandom key from the input keying material `ikm` with HMAC-BLAKE2b-512 as
+/// described in RFC 5869.  An empty salt is replaced by 64 zero bytes.
+pub fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    if salt.is_empty() {
+        hmac::hmac(&[0; PRKBYTES], ikm)
+    } else {
+        hmac::hmac(salt, ikm)
+    }
+}
+
+/// Expand the pseudorandom key `prk` into `length` bytes of output keying material bound to
+/// `info` with HMAC-BLAKE2b-512 as described in RFC 5869.
+pub fn hkdf_expand(prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, String> {
+    let state = Hmac::new(prk);
+
+    expand_with(info, length, |parts| {
+        let mut state = state.clone();
+        for part in parts {
+            state.update(part);
+        }
+        Ok(state.finalize())
+    })
+}
+
+/// Derive `length` bytes of output keying material from `ikm`, `salt` and `info` with
+/// HKDF-BLAKE2b-512 as described in RFC 5869.
+pub fn hkdf(ikm: &[u8], salt: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, String> {
+    let mut prk = hkdf_extract(salt, ikm);
+    let okm = hkdf_expand(&prk, info, length);
+    zeroize(&mut prk);
+
+    okm
+}
+
+/// Derive `dklen` bytes from `password` and `salt` with PBKDF2 as described in RFC 8018, using
+/// HMAC-BLAKE2b-512 as the pseudorandom function and running it `iterations` times per output
+/// block.  The result is the same as Python's `hashlib.pbkdf2_hmac("blake2b512", ...)`.
+pub fn pbkdf2_hmac_blake2b(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    dklen: usize,
+) -> Result<Vec<u8>, String> {
+    if iterations == 0 {
+        return Err("iteration count must be at least 1, got: 0".to_string());
+    }
+    let max_dklen = u64::from(u32::MAX) * hmac::OUTBYTES as u64;
+    if dklen == 0 || dklen as u64 > max_dklen {
+        return Err(format!(
+            "derived key length must be between 1 and {} bytes, got: {}",
+            max_dklen, dklen,
+        ));
+    }
+
+    let state = Hmac::new(password);
+    let prf = |parts: &[&[u8]]| {
+        let mut state = state.clone();
+        for part in parts {
+            state.update(part);
+        }
+        state.finalize()
+    };
+
+    let mut dk = Vec::with_capacity(dklen + hmac::OUTBYTES);
+    let mut index = 1u32;
+    while dk.len() < dklen {
+        let mut u = prf(&[salt, &index.to_be_bytes()]);
+        let mut t = u.clone();
+        for _ in 1..iterations {
+            let next = prf(&[&u]);
+            zeroize(&mut u);
+            u = next;
+
+            for (x, y) in t.iter_mut().zip(u.iter()) {
+                *x ^= y;
+            }
+        }
+        zeroize(&mut u);
+
+        dk.extend_from_slice(&t);
+        zeroize(&mut t);
+        index = index.wrapping_add(1);
+    }
+    zeroize(&mut dk[dklen..]);
+    dk.truncate(dklen);
+
+    Ok(dk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Example {
+        ikm: &'static str,
+        salt: &'static str,
+        info: &'static str,
+        length: usize,
+        prk: &'static str,
+        okm: &'static str,
+    }
+
+    /// The inputs of the test cases from RFC 5869 appendix A with outputs for the native keyed
+    /// mode KDF
+    const KEYED_EXAMPLES: &[Example] = &[
+        Example {
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: "000102030405060708090a0b0c",
+            info: "f0f1f2f3f4f5f6f7f8f9",
+            length: 42,
+            prk: "cfdde0bc0d49c85bed1fd6c8696d1524c7ad7fa1fd3531a242ef8244298b7cf2df7142168b43fac141948e0b7a6f498cff7d805d5d8c819b7c06eca8e60f122e",
+            okm: "18eca4e83bda55e1a926e5dfd8869bb9f02f5de7772796f7e38350921f6cff0b7d160f6812cb1fde8a64",
+        },
+        Example {
+            ikm: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f",
+            salt: "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+            info: "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+            length: 82,
+            prk: "1adf35c391c0955e274caa90befbf69cd0c5336147c2a2fe616cb11a9647e4f7ae9f7fbdccd8a4dcd6c95efc5499000294088d3fe56d4e3b2c08c68a368b0644",
+            okm: "4746dc89f46071a8e42b21fe738100a586ea6e2f8f4edafcc39bf5d5f0483f04ac047a9c422b22b26a1a895ad7a20d0d9c2d6b45343afa42f33b22f136f9d79790128eea10ec3cd493e4afcfa8f4efa59186",
+        },
+        Example {
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: "",
+            info: "",
+            length: 42,
+            prk: "312852be4511209c77dfe98dcc3773d5b2de9cc8020ab3da65600c2b93cf3182da95f35a7941a8f8ec6500f81ba66c07249e68af9e4e1aebf31815a67a9e30d3",
+            okm: "0aad709babc52bc04596ee1d80741a999a0828c95c38faeda79c984961d6b5ec50805e48bc7065d24f2f",
+        },
+    ];
+
+    /// The inputs of the test cases from RFC 5869 appendix A with outputs for HKDF-BLAKE2b-512
+    const HKDF_EXAMPLES: &[Example] = &[
+        Example {
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: "000102030405060708090a0b0c",
+            info: "f0f1f2f3f4f5f6f7f8f9",
+            length: 42,
+            prk: "02fbaa4ced1e659fe2eb8ae358de5be0edc0fd4526dbc7cc68d2ab9273e1b230ab9d6860f65dc7bad92a483c0f90e019ace68b5e4fe65251666eb1e71e57a812",
+            okm: "8815e1a85b5e90e6174323fdd180248887a7138af6dc5c8320fde21a60a078808267d6a41b6a938d7b30",
+        },
+        Example {
+            ikm: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f",
+            salt: "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+            info: "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+            length: 82,
+            prk: "9eddca55c30bd643eb09b91a6a7b2763096333338d4fd2145899d746b415c07422c9ff0aecdefa0fb1cae869492a499ad01f9e0a1a29b13067a97805455ad54f",
+            okm: "bb19eccde3ff3f41a8b3a147eb4fa640599a4e2194aadd98c0458f7ec05ac995238f9b6002d5bcbfa7c67975965de73ff014e55acff823162d6d5c04498501b1acb5e99058466d1a440ffc544f408e89f555",
+        },
+        Example {
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: "",
+            info: "",
+            length: 42,
+            prk: "39db468c9289015fd163c86a299ef5a95942835aed7103a2b9f30cc796e2d0586fc284a024c25fb18699c78e47273346fa35f076ac315e5906fe170ba1877c63",
+            okm: "817520332f597bd8f557a4b40fddfe7674f1edac6c8a1a36fa0546b649bfae4a2ed3f34d03fdef572d51",
+        },
+    ];
+
+    #[test]
+    fn test_kdf_keyed() {
+        for example in KEYED_EXAMPLES {
+            let ikm = hex::decode(example.ikm).unwrap();
+            let salt = hex::decode(example.salt).unwrap();
+            let info = hex::decode(example.info).unwrap();
+
+            let prk = extract(&salt, &ikm);
+            assert_eq!(hex::encode(&prk), example.prk);
+
+            let okm = expand(&prk, &info, example.length).unwrap();
+            assert_eq!(hex::encode(&okm), example.okm);
+
+            let okm = derive(&ikm, &salt, &info, example.length).unwrap();
+            assert_eq!(hex::encode(&okm), example.okm);
+        }
+    }
+
+    #[test]
+    fn test_kdf_hkdf() {
+        for example in HKDF_EXAMPLES {
+            let ikm = hex::decode(example.ikm).unwrap();
+            let salt = hex::decode(example.salt).unwrap();
+            let info = hex::decode(example.info).unwrap();
+
+            let prk = hkdf_extract(&salt, &ikm);
+            assert_eq!(hex::encode(&prk), example.prk);
+
+            let okm = hkdf_expand(&prk, &info, example.length).unwrap();
+            assert_eq!(hex::encode(&okm), example.okm);
+
+            let okm = hkdf(&ikm, &salt, &info, example.length).unwrap();
+            assert_eq!(hex::encode(&okm), example.okm);
+        }
+    }
+
+    struct Pbkdf2Example {
+        password: &'static str,
+        salt: &'static str,
+        iterations: u32,
+        dklen: usize,
+        dk: &'static str,
+    }
+
+    /// The inputs of the test cases from RFC 6070 and a long password with outputs for
+    /// PBKDF2-HMAC-BLAKE2b-512, checked against Python's `hashlib.pbkdf2_hmac`
+    const PBKDF2_EXAMPLES: &[Pbkdf2Example] = &[
+        Pbkdf2Example {
+            password: "70617373776f7264",
+            salt: "73616c74",
+            iterations: 1,
+            dklen: 64,
+            dk: "684e7cc1dd9b241d2c977f38a896645da49b85eb13cf8f5c021efc167aad799343c06f50e2959de06a0bca80a154457d8e92e70ebdcdb3722dcf9badd6ff1dfb",
+        },
+        Pbkdf2Example {
+            password: "70617373776f7264",
+            salt: "73616c74",
+            iterations: 2,
+            dklen: 64,
+            dk: "40b77cc2ee4b4c44eeb5babc299be14af5670e39ea3ce14c0fe70e6c99369886ab4d693bad8bd811ed64c5cf65a4cc5260993e17bbf2423c77164752fcbf5a60",
+        },
+        Pbkdf2Example {
+            password: "70617373776f7264",
+            salt: "73616c74",
+            iterations: 4096,
+            dklen: 64,
+            dk: "9d4f324ef40b5be658fa0ab94a168664f060c0c9cc85a02ac83f2d44088cb7e7b812ef60e9b1673d4fd77240a68607d72b912e18a0ea4772f476be7583b66970",
+        },
+        Pbkdf2Example {
+            password: "70617373776f726450415353574f524470617373776f7264",
+            salt: "73616c7453414c5473616c7453414c5473616c7453414c5473616c7453414c5473616c74",
+            iterations: 4096,
+            dklen: 100,
+            dk: "a46b5335dbdda3d25d19bb11feddd99e452a7c34474198ca3174b63422ac83b0386ef5930ff516460b97dc6c275be725c2cbec5002c6528b34685365f51f55296bb9cec738087824f221912084822514d235cb86c2f3625b4e38729301ac9fbf3535cb5b",
+        },
+        Pbkdf2Example {
+            password: "7061737300776f7264",
+            salt: "7361006c74",
+            iterations: 4096,
+            dklen: 16,
+            dk: "f6c776d6265b53e32fa746713464fe59",
+        },
+    ];
+
+    #[test]
+    fn test_kdf_pbkdf2() {
+        for example in PBKDF2_EXAMPLES {
+            let password = hex::decode(example.password).unwrap();
+            let salt = hex::decode(example.salt).unwrap();
+
+            let dk =
+                pbkdf2_hmac_blake2b(&password, &salt, example.iterations, example.dklen).unwrap();
+            assert_eq!(hex::encode(&dk), example.dk);
+        }
+
+        // A password longer than a block is hashed first, and three blocks of output
+        let dk = pbkdf2_hmac_blake2b(&[b'k'; 200], b"", 3, 130).unwrap();
+        assert_eq!(
+            hex::encode(&dk),
+            "84abb9cdbe8bc2f3d6fbb5d4e48355eadd6fb76a3f8230ab484d3496c5fc75e474fd82ec59d4c7b4e2d6e12cc4581b53f25484141e521266fa4cc7d877a3d12b1d33345706296ac063b057c172fe6a4cbe1946774b0e54750973010a219363e820dcd54151dea06d1a051c397de946904c1349ba35e0fbf5352304dde0c24afffbef",
+        );
+    }
+
+    #[test]
+    fn test_kdf_error() {
+        let prk = [0u8; PRKBYTES];
+
+        assert!(expand(&prk, b"", MAX_OUTPUT_LENGTH + 1).is_err());
+        assert!(expand(&[0u8; PRKBYTES + 1], b"", 32).is_err());
+        assert!(hkdf_expand(&prk, b"", MAX_OUTPUT_LENGTH + 1).is_err());
+        assert!(pbkdf2_hmac_blake2b(b"password", b"salt", 0, 64).is_err());
+        assert!(pbkdf2_hmac_blake2b(b"password", b"salt", 1, 0).is_err());
+        assert_eq!(
+            expand(&prk, b"", MAX_OUTPUT_LENGTH).unwrap().len(),
+            MAX_OUTPUT_LENGTH
+        );
+    }
+}
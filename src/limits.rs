@@ -0,0 +1,293 @@
+//! A policy layer bounding the work a single compression function call may do.
+//!
+//! The number of rounds in blake2 F precompile input is an arbitrary `u32`, so one malicious input
+//! can keep a core busy for minutes.  `Limits` rejects such calls before `F` is run, and stops
+//! calls that run past their time budget anyway.
+
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::blake2b::{self, CompressArgs, F};
+
+/// Limits on a single compression function call.  The default has no limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// The largest number of rounds allowed.
+    pub max_rounds: Option<usize>,
+    /// The longest the call is allowed to take.  Calls are rejected up front if their running
+    /// time, estimated from the number of rounds and the speed of `F` on this machine, would be
+    /// over the budget.  Calls that get past the estimate are still abandoned once the budget has
+    /// run out, which `Limits::compress` and `Limits::permute` check every `ROUNDS_PER_CHECK`
+    /// rounds.  Both need `std::time::Instant`, which panics on `wasm32-unknown-unknown`.
+    pub time_budget: Option<Duration>,
+}
+
+/// The error returned when a call would exceed its `Limits`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LimitExceeded {
+    Rounds {
+        rounds: usize,
+        max_rounds: usize,
+    },
+    TimeBudget {
+        rounds: usize,
+        estimate: Duration,
+        time_budget: Duration,
+    },
+    TimeBudgetElapsed {
+        rounds: usize,
+        time_budget: Duration,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitExceeded::Rounds { rounds, max_rounds } => write!(
+                f,
+                "number of rounds must be at most {}, got: {}",
+                max_rounds, rounds
+            ),
+            LimitExceeded::TimeBudget {
+                rounds,
+                estimate,
+                time_budget,
+            } => write!(
+                f,
+                "{} rounds would take about {:?}, over the time budget of {:?}",
+                rounds, estimate, time_budget
+            ),
+            LimitExceeded::TimeBudgetElapsed {
+                rounds,
+                time_budget,
+            } => write!(
+                f,
+                "{} rounds took longer than the time budget of {:?}",
+                rounds, time_budget
+            ),
+        }
+    }
+}
+
+impl From<LimitExceeded> for String {
+    fn from(err: LimitExceeded) -> String {
+        err.to_string()
+    }
+}
+
+static DEFAULT_LIMITS: RwLock<Limits> = RwLock::new(Limits {
+    max_rounds: None,
+    time_budget: None,
+});
+
+/// The process-wide limits used when a call doesn't give its own.
+pub fn default_limits() -> Limits {
+    *DEFAULT_LIMITS.read().unwrap()
+}
+
+/// Set the process-wide limits used when a call doesn't give its own.
+pub fn set_default_limits(limits: Limits) {
+    *DEFAULT_LIMITS.write().unwrap() = limits;
+}
+
+/// The number of rounds run between checks of the time budget.  Small enough that a call overruns
+/// its budget by well under a millisecond, large enough that checking the clock costs nothing.
+pub const ROUNDS_PER_CHECK: usize = 1 << 14;
+
+/// The time one round of `F` takes on this machine, measured on first use.
+fn time_per_round() -> Duration {
+    static TIME_PER_ROUND: OnceLock<Duration> = OnceLock::new();
+    const ROUNDS: u32 = 10_000;
+
+    *TIME_PER_ROUND.get_or_init(|| {
+        // Take the fastest of a few runs to keep scheduling noise out of the estimate
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                F(ROUNDS as usize, &blake2b::IV, &[0; 16], &[0; 2], false);
+                start.elapsed() / ROUNDS
+            })
+            .min()
+            .unwrap()
+    })
+}
+
+impl Limits {
+    /// Check whether a call running `rounds` rounds of `F` is within the limits.
+    pub fn check(&self, rounds: usize) -> Result<(), LimitExceeded> {
+        if let Some(max_rounds) = self.max_rounds {
+            if rounds > max_rounds {
+                return Err(LimitExceeded::Rounds { rounds, max_rounds });
+            }
+        }
+        if let Some(time_budget) = self.time_budget {
+            let estimate = time_per_round().saturating_mul(rounds.min(u32::MAX as usize) as u32);
+            if estimate > time_budget {
+                return Err(LimitExceeded::TimeBudget {
+                    rounds,
+                    estimate,
+                    time_budget,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run the compression function over `args`, unless doing so would exceed the limits.
+    pub fn compress(&self, args: &CompressArgs) -> Result<[u8; 64], LimitExceeded> {
+        self.check(args.rounds)?;
+
+        match self.deadline() {
+            None => Ok(args.compress()),
+            Some(deadline) => {
+                compress_before(args, deadline).ok_or_else(|| self.elapsed(args.rounds))
+            }
+        }
+    }
+
+    /// Run `rounds` rounds of the keyless permutation over `v`, unless doing so would exceed the
+    /// limits.  `v` is left partly permuted if the time budget runs out.
+    pub fn permute(&self, v: &mut [u64; 16], rounds: usize) -> Result<(), LimitExceeded> {
+        self.check(rounds)?;
+
+        let finished = match self.deadline() {
+            None => {
+                blake2b::permute(v, rounds);
+                true
+            }
+            Some(deadline) => permute_before(v, rounds, deadline),
+        };
+        if !finished {
+            return Err(self.elapsed(rounds));
+        }
+
+        Ok(())
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.time_budget
+            .and_then(|time_budget| Instant::now().checked_add(time_budget))
+    }
+
+    fn elapsed(&self, rounds: usize) -> LimitExceeded {
+        LimitExceeded::TimeBudgetElapsed {
+            rounds,
+            time_budget: self.time_budget.unwrap(),
+        }
+    }
+}
+
+/// Run the compression function over `args`, giving up if it is still running at `deadline`.
+fn compress_before(args: &CompressArgs, deadline: Instant) -> Option<[u8; 64]> {
+    blake2b::F_batched(args, ROUNDS_PER_CHECK, || Instant::now() < deadline)
+}
+
+/// Permute `v` for `rounds` rounds, giving up if it is still running at `deadline`.  Returns
+/// whether all rounds were run.
+fn permute_before(v: &mut [u64; 16], rounds: usize, deadline: Instant) -> bool {
+    // Every round of the permutation is the same, so it can be run in pieces
+    let mut done = 0;
+    while done < rounds {
+        if done > 0 && Instant::now() >= deadline {
+            return false;
+        }
+        let batch = ROUNDS_PER_CHECK.min(rounds - done);
+        blake2b::permute(v, batch);
+        done += batch;
+    }
+
+    true
+}
+
+/// Decode blake2 precompile input from `input` and run the compression function over it, unless
+/// doing so would exceed `limits`.
+pub fn decode_and_compress(input: &[u8], limits: &Limits) -> Result<[u8; 64], String> {
+    let args = blake2b::decode_parameters(input)?;
+
+    Ok(limits.compress(&args)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EIP-152 test vector 5, with the number of rounds replaced
+    fn input(rounds: u32) -> Vec<u8> {
+        let mut input = hex::decode("0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001").unwrap();
+        input[..4].copy_from_slice(&rounds.to_be_bytes());
+        input
+    }
+
+    #[test]
+    fn test_limits_max_rounds() {
+        let limits = Limits {
+            max_rounds: Some(12),
+            ..Limits::default()
+        };
+
+        assert_eq!(hex::encode(decode_and_compress(&input(12), &limits).unwrap()), "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923");
+        assert_eq!(
+            limits.check(13),
+            Err(LimitExceeded::Rounds {
+                rounds: 13,
+                max_rounds: 12
+            }),
+        );
+        assert_eq!(
+            decode_and_compress(&input(u32::MAX), &limits),
+            Err("number of rounds must be at most 12, got: 4294967295".into()),
+        );
+    }
+
+    #[test]
+    fn test_limits_time_budget() {
+        let limits = Limits {
+            time_budget: Some(Duration::from_millis(100)),
+            ..Limits::default()
+        };
+
+        // Checking happens before F is run, so this returns at once rather than after minutes
+        let start = Instant::now();
+        match limits.check(u32::MAX as usize) {
+            Err(LimitExceeded::TimeBudget { estimate, .. }) => {
+                assert!(estimate > Duration::from_millis(100))
+            }
+            result => panic!("expected a time budget error, got: {:?}", result),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(limits.check(0).is_ok());
+    }
+
+    #[test]
+    fn test_limits_deadline() {
+        let limits = Limits {
+            time_budget: Some(Duration::from_secs(60)),
+            ..Limits::default()
+        };
+        let args = blake2b::decode_parameters(&input(12)).unwrap();
+        assert_eq!(limits.compress(&args), Ok(args.compress()));
+
+        let mut expected = [1; 16];
+        blake2b::permute(&mut expected, 3 * ROUNDS_PER_CHECK + 1);
+        let mut v = [1; 16];
+        assert_eq!(limits.permute(&mut v, 3 * ROUNDS_PER_CHECK + 1), Ok(()));
+        assert_eq!(v, expected);
+
+        // Past the deadline only the first batch runs
+        let past = Instant::now();
+        let rounds = 2 * ROUNDS_PER_CHECK;
+        assert_eq!(
+            compress_before(&CompressArgs { rounds, ..args }, past),
+            None
+        );
+        assert!(!permute_before(&mut v, rounds, past));
+        assert!(permute_before(&mut v, ROUNDS_PER_CHECK, past));
+    }
+
+    #[test]
+    fn test_limits_default() {
+        assert_eq!(Limits::default().check(u32::MAX as usize), Ok(()));
+    }
+}
@@ -0,0 +1,168 @@
+//! A fixed size pool of worker threads for running long compression function calls off the
+//! calling thread.
+
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A pool of worker threads that run queued jobs in the order they were submitted.  At most
+/// `workers` jobs run at once and at most `queue_size` more wait for a worker, which bounds the
+/// load and memory a flood of submissions can cause.  Submitting to a full queue fails rather than
+/// blocking, so callers holding a lock the jobs need can't deadlock.
+///
+/// Dropping the pool waits for the queued jobs to run and the workers to exit.
+pub struct ThreadPool {
+    sender: Option<SyncSender<Job>>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// Start a pool of `workers` threads with room for `queue_size` jobs waiting to run.
+    pub fn new(workers: usize, queue_size: usize) -> Result<Self, String> {
+        if workers == 0 {
+            return Err("number of workers must be at least 1".into());
+        }
+
+        let (sender, receiver) = mpsc::sync_channel::<Job>(queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        // Dropping the pool on an error closes the channel and joins the workers started so far
+        let mut pool = ThreadPool {
+            sender: Some(sender),
+            handles: Vec::with_capacity(workers),
+        };
+        for i in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            let handle = thread::Builder::new()
+                .name(format!("blake2b-worker-{}", i))
+                .spawn(move || run_worker(&receiver))
+                .map_err(|err| format!("could not start worker thread: {}", err))?;
+            pool.handles.push(handle);
+        }
+
+        Ok(pool)
+    }
+
+    /// The number of worker threads in the pool.
+    pub fn workers(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Queue `job` to be run on one of the worker threads, or fail if the queue is full.
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) -> Result<(), String> {
+        let sender = self.sender.as_ref().unwrap();
+        match sender.try_send(Box::new(job)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err("too many jobs are queued on the worker pool".into()),
+            // Workers only exit once the sender has been dropped
+            Err(TrySendError::Disconnected(_)) => unreachable!(),
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Closing the channel lets the workers exit once they've run the queued jobs
+        drop(self.sender.take());
+        let current = thread::current().id();
+        for handle in self.handles.drain(..) {
+            // A job dropping the pool it runs on can't wait for itself
+            if handle.thread().id() != current {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // The lock is released before running the job so other workers can take the next one
+        let job = match receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        job();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+    use std::sync::Barrier;
+
+    #[test]
+    fn test_pool_runs_jobs() {
+        let pool = ThreadPool::new(3, 10).unwrap();
+        let (sender, receiver) = channel();
+
+        for i in 0..10 {
+            let sender = sender.clone();
+            pool.execute(move || sender.send(i).unwrap()).unwrap();
+        }
+        let mut results: Vec<i32> = receiver.iter().take(10).collect();
+        results.sort_unstable();
+
+        assert_eq!(pool.workers(), 3);
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_pool_runs_jobs_concurrently() {
+        // Both jobs must be running at once to get past the barrier
+        let pool = ThreadPool::new(2, 2).unwrap();
+        let barrier = Arc::new(Barrier::new(2));
+        let (sender, receiver) = channel();
+
+        for _ in 0..2 {
+            let (barrier, sender) = (Arc::clone(&barrier), sender.clone());
+            pool.execute(move || {
+                barrier.wait();
+                sender.send(()).unwrap();
+            })
+            .unwrap();
+        }
+
+        assert_eq!(receiver.iter().take(2).count(), 2);
+    }
+
+    #[test]
+    fn test_pool_queue_full() {
+        let pool = ThreadPool::new(1, 1).unwrap();
+        let (started, wait_started) = channel();
+        let (release, wait_release) = channel::<()>();
+        pool.execute(move || {
+            started.send(()).unwrap();
+            wait_release.recv().unwrap();
+        })
+        .unwrap();
+        wait_started.recv().unwrap();
+
+        // The worker is busy, so one job fits in the queue and the next is refused
+        assert!(pool.execute(|| ()).is_ok());
+        assert!(pool.execute(|| ()).is_err());
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn test_pool_drop_runs_queued_jobs() {
+        let pool = ThreadPool::new(1, 10).unwrap();
+        let (sender, receiver) = channel();
+        for i in 0..10 {
+            let sender = sender.clone();
+            pool.execute(move || sender.send(i).unwrap()).unwrap();
+        }
+        drop(sender);
+        drop(pool);
+
+        // Every job has run by the time the pool is dropped
+        assert_eq!(receiver.try_iter().count(), 10);
+    }
+
+    #[test]
+    fn test_pool_error() {
+        assert!(ThreadPool::new(0, 1).is_err());
+    }
+}
@@ -0,0 +1,1783 @@
+use crate::hasher::{self, Params};
+use crate::pool::ThreadPool;
+use crate::zeroize::zeroize;
+use crate::{blake2b, file, generichash, hmac, kdf, limits, verify};
+
+use std::convert::TryInto;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+
+type CompressTuple = (usize, Vec<u64>, Vec<u64>, Vec<u64>, bool);
+
+/// A byte string extracted from Python like ``Vec<u8>`` that is wiped from memory when dropped,
+/// for keys, passwords and other secret inputs.
+#[derive(Default)]
+struct SecretBytes(Vec<u8>);
+
+impl<'py> FromPyObject<'_, 'py> for SecretBytes {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'_, 'py, PyAny>) -> PyResult<Self> {
+        Ok(SecretBytes(obj.extract()?))
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        zeroize(&mut self.0);
+    }
+}
+
+/// Copy the derived key or other secret `out` into a Python ``bytes`` object, wiping the Rust
+/// copy.
+fn secret_bytes(py: Python, mut out: Vec<u8>) -> Py<PyAny> {
+    let bytes = PyBytes::new(py, &out).into();
+    zeroize(&mut out);
+
+    bytes
+}
+
+fn compress_tuple(args: &blake2b::CompressArgs) -> CompressTuple {
+    (
+        args.rounds,
+        args.h.to_vec(),
+        args.m.to_vec(),
+        args.t.to_vec(),
+        args.f,
+    )
+}
+
+/// Decode parameters for the ``compress`` function from the tightly packed
+/// encoding in the byte sequence `input`.
+///
+/// Parameters
+/// ----------
+/// input : bytes, List[int]
+///     A vector of 213 bytes representing the tightly encoded input.
+///
+/// Returns
+/// ----------
+/// out : (int, List[int], List[int], List[int], bool)
+///     A tuple of parameters to pass to the ``compress`` function.
+#[pyfunction]
+fn decode_parameters(input: Vec<u8>) -> PyResult<CompressTuple> {
+    let result = blake2b::decode_parameters(&input);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(args) => Ok(compress_tuple(&args)),
+    }
+}
+
+/// The input to the blake2b compression function: the number of `rounds`, the
+/// starting state `h`, the message block `m`, the offset counters `t` and the
+/// final block flag `f`.  Create one with ``from_bytes``, ``block`` or
+/// ``from_tuple``.
+#[pyclass(eq)]
+#[derive(PartialEq)]
+struct CompressArgs {
+    args: blake2b::CompressArgs,
+}
+
+fn words<A: Default + AsMut<[u64]>>(name: &str, words: &[u64]) -> PyResult<A> {
+    let mut out = A::default();
+    if words.len() != out.as_mut().len() {
+        return Err(PyValueError::new_err(format!(
+            "{} should have exactly {} words, got: {}",
+            name,
+            out.as_mut().len(),
+            words.len()
+        )));
+    }
+    out.as_mut().copy_from_slice(words);
+
+    Ok(out)
+}
+
+#[pymethods]
+impl CompressArgs {
+    /// Decodes the tightly packed encoding in the byte sequence `input`, like
+    /// ``decode_parameters``.
+    ///
+    /// Parameters
+    /// ----------
+    /// input : bytes
+    ///     A vector of 213 bytes representing the tightly encoded input.
+    ///
+    /// Returns
+    /// -------
+    /// out : CompressArgs
+    #[staticmethod]
+    fn from_bytes(input: &[u8]) -> PyResult<CompressArgs> {
+        match blake2b::CompressArgs::from_bytes(input) {
+            Err(msg) => Err(PyValueError::new_err(msg)),
+            Ok(args) => Ok(CompressArgs { args }),
+        }
+    }
+
+    /// Creates input for compressing a block of 128 raw bytes, read as 16
+    /// little endian words.
+    ///
+    /// Parameters
+    /// ----------
+    /// block : bytes
+    ///     The 128 byte message block.
+    /// rounds : int
+    ///     The number of rounds of mixing.
+    /// h : List[int], optional
+    ///     The 8 word starting state.  Defaults to the blake2b IV.
+    /// t : List[int], optional
+    ///     The 2 word offset counters.  Defaults to zero.
+    /// f : bool
+    ///     The final block flag.
+    ///
+    /// Returns
+    /// -------
+    /// out : CompressArgs
+    #[staticmethod]
+    #[pyo3(signature = (block, rounds = 12, h = None, t = None, f = false))]
+    fn block(
+        block: &[u8],
+        rounds: usize,
+        h: Option<Vec<u64>>,
+        t: Option<Vec<u64>>,
+        f: bool,
+    ) -> PyResult<CompressArgs> {
+        let h = match h {
+            Some(h) => words("h", &h)?,
+            None => blake2b::IV,
+        };
+        let t = match t {
+            Some(t) => words("t", &t)?,
+            None => [0; 2],
+        };
+
+        match blake2b::CompressArgs::block(rounds, h, block, t, f) {
+            Err(msg) => Err(PyValueError::new_err(msg)),
+            Ok(args) => Ok(CompressArgs { args }),
+        }
+    }
+
+    /// Creates input from a ``(rounds, h, m, t, f)`` tuple as returned by
+    /// ``decode_parameters``.
+    ///
+    /// Parameters
+    /// ----------
+    /// args : (int, List[int], List[int], List[int], bool)
+    ///
+    /// Returns
+    /// -------
+    /// out : CompressArgs
+    #[staticmethod]
+    fn from_tuple(args: CompressTuple) -> PyResult<CompressArgs> {
+        let (rounds, h, m, t, f) = args;
+        let args = blake2b::CompressArgs {
+            rounds,
+            h: words("h", &h)?,
+            m: words("m", &m)?,
+            t: words("t", &t)?,
+            f,
+        };
+
+        Ok(CompressArgs { args })
+    }
+
+    /// Returns the ``(rounds, h, m, t, f)`` tuple returned by
+    /// ``decode_parameters``, which can be passed on to ``compress``.
+    fn to_tuple(&self) -> CompressTuple {
+        compress_tuple(&self.args)
+    }
+
+    /// Returns the 213 byte tightly packed encoding read by ``from_bytes``.
+    fn to_bytes(&self, py: Python) -> PyResult<Py<PyAny>> {
+        if self.args.rounds > u32::MAX as usize {
+            return Err(PyValueError::new_err(format!(
+                "number of rounds should fit in 32 bits, got: {}",
+                self.args.rounds
+            )));
+        }
+
+        Ok(PyBytes::new(py, &self.args.to_bytes()).into())
+    }
+
+    /// Runs the compression function over this input.  See ``compress``.
+    ///
+    /// Returns
+    /// -------
+    /// out : bytes
+    ///     A vector of 64 bytes representing the blake2b hash of the input data.
+    #[pyo3(signature = (max_rounds = None, time_budget = None))]
+    fn compress(
+        &self,
+        py: Python,
+        max_rounds: Option<usize>,
+        time_budget: Option<f64>,
+    ) -> PyResult<Py<PyAny>> {
+        let limits = call_limits(max_rounds, time_budget)?;
+        let result = limits.compress(&self.args);
+
+        Ok(PyBytes::new(py, &result.map_err(limit_exceeded)?).into())
+    }
+
+    #[getter]
+    fn rounds(&self) -> usize {
+        self.args.rounds
+    }
+
+    #[getter]
+    fn h(&self) -> Vec<u64> {
+        self.args.h.to_vec()
+    }
+
+    #[getter]
+    fn m(&self) -> Vec<u64> {
+        self.args.m.to_vec()
+    }
+
+    #[getter]
+    fn t(&self) -> Vec<u64> {
+        self.args.t.to_vec()
+    }
+
+    #[getter]
+    fn f(&self) -> bool {
+        self.args.f
+    }
+
+    fn __repr__(&self) -> String {
+        let blake2b::CompressArgs { rounds, h, m, t, f } = self.args;
+        let f = if f { "True" } else { "False" };
+
+        format!(
+            "CompressArgs(rounds={}, h={:?}, m={:?}, t={:?}, f={})",
+            rounds, h, m, t, f
+        )
+    }
+}
+
+/// Calculates a blake2b hash for the given message block.
+///
+/// Parameters
+/// ----------
+/// rounds : int
+///     The number of rounds of mixing to occur during hashing.
+/// starting_state : List[int]
+///     A vector of 8 64-bit integers representing the starting state of the
+///     hash function.
+/// block : List[int]
+///     A vector of 16 64-bit integers representing the message block to be hashed.
+/// offset_counters : List[int]
+///     A vector of 2 64-bit integers representing the message byte offset at
+///     the end of the current block.
+/// final_block_flag : bool
+///     A flag indicating the final block of the message.
+/// max_rounds : int, optional
+///     The largest number of rounds allowed.  Defaults to the limit set with
+///     ``set_limits``.
+/// time_budget : float, optional
+///     The longest the call is allowed to take in seconds.  Defaults to the
+///     limit set with ``set_limits``.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of 64 bytes representing the blake2b hash of the input data.
+///
+/// Raises
+/// ------
+/// LimitExceeded
+///     If `rounds` is over either limit, or the call runs out of time.  No hash
+///     is returned.
+#[allow(clippy::too_many_arguments)]
+#[pyfunction]
+#[pyo3(signature = (
+    rounds, starting_state, block, offset_counters, final_block_flag, max_rounds = None, time_budget = None
+))]
+fn compress(
+    py: Python,
+    rounds: usize,
+    starting_state: Vec<u64>,
+    block: Vec<u64>,
+    offset_counters: Vec<u64>,
+    final_block_flag: bool,
+    max_rounds: Option<usize>,
+    time_budget: Option<f64>,
+) -> PyResult<Py<PyAny>> {
+    let limits = call_limits(max_rounds, time_budget)?;
+    let args = compress_args(
+        rounds,
+        &starting_state,
+        &block,
+        &offset_counters,
+        final_block_flag,
+    )?;
+    let result = limits.compress(&args);
+
+    Ok(PyBytes::new(py, &result.map_err(limit_exceeded)?).into())
+}
+
+/// Check the lengths of the vectors passed to ``compress`` and collect them into input to `F`.
+fn compress_args(
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+) -> PyResult<blake2b::CompressArgs> {
+    blake2b::check_lengths(starting_state, block, offset_counters)
+        .map_err(PyValueError::new_err)?;
+
+    Ok(blake2b::CompressArgs {
+        rounds,
+        h: starting_state.try_into().unwrap(),
+        m: block.try_into().unwrap(),
+        t: offset_counters.try_into().unwrap(),
+        f: final_block_flag,
+    })
+}
+
+/// Decode `input` and check the number of rounds against `limits`, so that the compression
+/// function can be run on the result.
+fn checked_decode(input: &[u8], limits: &limits::Limits) -> PyResult<blake2b::CompressArgs> {
+    let args = blake2b::decode_parameters(input).map_err(PyValueError::new_err)?;
+    check_limits(limits, args.rounds)?;
+
+    Ok(args)
+}
+
+/// Calculates a blake2b hash for the tightly encoded input given in the byte
+/// sequence `input`.
+///
+/// Parameters
+/// ----------
+/// input : bytes, List[int]
+///     A vector of 213 bytes representing the tightly encoded input.
+/// max_rounds : int, optional
+///     The largest number of rounds allowed.  Defaults to the limit set with
+///     ``set_limits``.
+/// time_budget : float, optional
+///     The longest the call is allowed to take in seconds.  Defaults to the
+///     limit set with ``set_limits``.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of 64 bytes representing the blake2b hash of the input data.
+///
+/// Raises
+/// ------
+/// LimitExceeded
+///     If the number of rounds is over either limit, or the call runs out of
+///     time.  No hash is returned.
+#[pyfunction]
+#[pyo3(signature = (input, max_rounds = None, time_budget = None))]
+fn decode_and_compress(
+    py: Python,
+    input: Vec<u8>,
+    max_rounds: Option<usize>,
+    time_budget: Option<f64>,
+) -> PyResult<Py<PyAny>> {
+    let limits = call_limits(max_rounds, time_budget)?;
+    let args = checked_decode(&input, &limits)?;
+    let result = limits.compress(&args);
+
+    Ok(PyBytes::new(py, &result.map_err(limit_exceeded)?).into())
+}
+
+create_exception!(blake2b, LimitExceeded, PyValueError);
+
+fn limit_exceeded(err: limits::LimitExceeded) -> PyErr {
+    LimitExceeded::new_err(err.to_string())
+}
+
+fn check_limits(limits: &limits::Limits, rounds: usize) -> PyResult<()> {
+    limits.check(rounds).map_err(limit_exceeded)
+}
+
+fn duration_from_secs(seconds: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        PyValueError::new_err(format!(
+            "time budget must be a non-negative number of seconds, got: {}",
+            seconds
+        ))
+    })
+}
+
+/// The process-wide limits with `max_rounds` and `time_budget` replaced where given.
+fn call_limits(max_rounds: Option<usize>, time_budget: Option<f64>) -> PyResult<limits::Limits> {
+    let mut limits = limits::default_limits();
+    if max_rounds.is_some() {
+        limits.max_rounds = max_rounds;
+    }
+    if let Some(seconds) = time_budget {
+        limits.time_budget = Some(duration_from_secs(seconds)?);
+    }
+
+    Ok(limits)
+}
+
+/// Sets the process-wide limits on the number of rounds that
+/// ``decode_and_compress``, ``compress`` and their ``*_async`` variants will
+/// run.  Calls over a limit raise ``LimitExceeded`` instead of calculating the
+/// hash.  ``None`` means no limit, which is the default.
+///
+/// Parameters
+/// ----------
+/// max_rounds : int, optional
+///     The largest number of rounds allowed.
+/// time_budget : float, optional
+///     The longest a call is allowed to take in seconds.  Calls whose running
+///     time, estimated from the number of rounds and the speed of the
+///     compression function on this machine, is over the budget are rejected
+///     at once.  Calls that run past it anyway are stopped within a fraction
+///     of a millisecond.
+#[pyfunction]
+#[pyo3(signature = (max_rounds = None, time_budget = None))]
+fn set_limits(max_rounds: Option<usize>, time_budget: Option<f64>) -> PyResult<()> {
+    let time_budget = match time_budget {
+        None => None,
+        Some(seconds) => Some(duration_from_secs(seconds)?),
+    };
+    limits::set_default_limits(limits::Limits {
+        max_rounds,
+        time_budget,
+    });
+
+    Ok(())
+}
+
+/// Returns the process-wide limits set with ``set_limits``.
+///
+/// Returns
+/// -------
+/// out : (Optional[int], Optional[float])
+///     The maximum number of rounds and the time budget in seconds.
+#[pyfunction]
+fn get_limits() -> (Option<usize>, Option<f64>) {
+    let limits = limits::default_limits();
+    (
+        limits.max_rounds,
+        limits.time_budget.map(|budget| budget.as_secs_f64()),
+    )
+}
+
+/// An iterator over snapshots of the working vector of the blake2b
+/// compression function, created by ``compress_trace``.  Each item is a tuple
+/// ``(round, g, sigma, v)`` of the round number, the index of the ``G`` call
+/// just made (``None`` when tracing whole rounds), the SIGMA row used in the
+/// round and the 16 word working vector.
+#[pyclass]
+struct CompressTrace {
+    trace: blake2b::Trace,
+}
+
+type TraceItem = (usize, Option<usize>, Vec<usize>, Vec<u64>);
+
+#[pymethods]
+impl CompressTrace {
+    /// Returns the output of the compression function given the working
+    /// vector so far.  Once the iterator has been exhausted this is the same
+    /// as the output of ``compress``.
+    fn output(&self, py: Python) -> Py<PyAny> {
+        PyBytes::new(py, &self.trace.output()).into()
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> Option<TraceItem> {
+        slf.trace
+            .next()
+            .map(|step| (step.round, step.g, step.sigma.to_vec(), step.v.to_vec()))
+    }
+}
+
+/// Runs the blake2b compression function step by step for debugging,
+/// yielding the working vector after each ``G`` call or after each round.
+///
+/// Parameters
+/// ----------
+/// rounds : int
+///     The number of rounds of mixing to occur during hashing.
+/// starting_state : List[int]
+///     A vector of 8 64-bit integers representing the starting state of the
+///     hash function.
+/// block : List[int]
+///     A vector of 16 64-bit integers representing the message block to be hashed.
+/// offset_counters : List[int]
+///     A vector of 2 64-bit integers representing the message byte offset at
+///     the end of the current block.
+/// final_block_flag : bool
+///     A flag indicating the final block of the message.
+/// per_round : bool
+///     Yield the working vector once per round instead of after every ``G``
+///     call.
+///
+/// Returns
+/// -------
+/// out : CompressTrace
+///     An iterator of ``(round, g, sigma, v)`` tuples.
+#[pyfunction]
+#[pyo3(signature = (
+    rounds, starting_state, block, offset_counters, final_block_flag, per_round = false
+))]
+fn compress_trace(
+    rounds: usize,
+    starting_state: Vec<u64>,
+    block: Vec<u64>,
+    offset_counters: Vec<u64>,
+    final_block_flag: bool,
+    per_round: bool,
+) -> PyResult<CompressTrace> {
+    if let Err(msg) = blake2b::check_lengths(&starting_state, &block, &offset_counters) {
+        return Err(PyValueError::new_err(msg));
+    }
+
+    let level = if per_round {
+        blake2b::TraceLevel::Round
+    } else {
+        blake2b::TraceLevel::G
+    };
+    let trace = blake2b::F_trace(
+        rounds,
+        &starting_state,
+        &block,
+        &offset_counters,
+        final_block_flag,
+        level,
+    );
+
+    Ok(CompressTrace { trace })
+}
+
+/// The blake2b mixing function ``G``, which mixes the message words `x` and
+/// `y` into the words `a`, `b`, `c` and `d` of the working vector `v`.
+///
+/// Parameters
+/// ----------
+/// v : List[int]
+///     A vector of 16 64-bit integers representing the working vector.
+/// a, b, c, d : int
+///     Indices into `v` of the words to mix, each less than 16.
+/// x, y : int
+///     The 64-bit message words to mix in.
+///
+/// Returns
+/// -------
+/// out : List[int]
+///     The working vector after mixing.
+#[pyfunction]
+#[pyo3(name = "G")]
+fn mix(v: Vec<u64>, a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) -> PyResult<Vec<u64>> {
+    let mut v: [u64; 16] = words("v", &v)?;
+    if let Some(index) = [a, b, c, d].iter().find(|&&index| index >= v.len()) {
+        return Err(PyValueError::new_err(format!(
+            "indices should be less than 16, got: {}",
+            index
+        )));
+    }
+
+    blake2b::G(&mut v, a, b, c, d, x, y);
+
+    Ok(v.to_vec())
+}
+
+/// Runs round `r` of the blake2b compression function on the working vector
+/// `v`: ``G`` on the columns and then the diagonals, with the words of `m` in
+/// the order given by row ``r % 10`` of the message schedule.
+///
+/// Parameters
+/// ----------
+/// v : List[int]
+///     A vector of 16 64-bit integers representing the working vector.
+/// m : List[int]
+///     A vector of 16 64-bit integers representing the message block.
+/// r : int
+///     The round number.
+///
+/// Returns
+/// -------
+/// out : List[int]
+///     The working vector after the round.
+#[pyfunction]
+#[pyo3(name = "round")]
+fn round_function(v: Vec<u64>, m: Vec<u64>, r: usize) -> PyResult<Vec<u64>> {
+    let mut v: [u64; 16] = words("v", &v)?;
+    let m: [u64; 16] = words("m", &m)?;
+
+    blake2b::round(&mut v, &m, r);
+
+    Ok(v.to_vec())
+}
+
+/// The keyless blake2b permutation: `rounds` rounds of the compression
+/// function with an all zero message block, without its initialization or
+/// feed-forward.
+///
+/// Parameters
+/// ----------
+/// v : List[int]
+///     A vector of 16 64-bit integers representing the state.
+/// rounds : int
+///     The number of rounds to run.
+/// max_rounds : int, optional
+///     The largest number of rounds allowed.  Defaults to the limit set with
+///     ``set_limits``.
+/// time_budget : float, optional
+///     The longest the call is allowed to take in seconds.  Defaults to the
+///     limit set with ``set_limits``.
+///
+/// Returns
+/// -------
+/// out : List[int]
+///     The permuted state.
+///
+/// Raises
+/// ------
+/// LimitExceeded
+///     If `rounds` is over either limit, or the call runs out of time.  No
+///     state is returned.
+#[pyfunction]
+#[pyo3(signature = (v, rounds = 12, max_rounds = None, time_budget = None))]
+fn permute(
+    py: Python,
+    v: Vec<u64>,
+    rounds: usize,
+    max_rounds: Option<usize>,
+    time_budget: Option<f64>,
+) -> PyResult<Vec<u64>> {
+    let mut v: [u64; 16] = words("v", &v)?;
+    let limits = call_limits(max_rounds, time_budget)?;
+
+    py.detach(|| limits.permute(&mut v, rounds))
+        .map_err(limit_exceeded)?;
+
+    Ok(v.to_vec())
+}
+
+fn checked_salt_personal(name: &str, value: Option<Vec<u8>>) -> Result<Option<[u8; 16]>, String> {
+    match value {
+        None => Ok(None),
+        Some(bytes) => {
+            if bytes.len() != 16 {
+                return Err(format!(
+                    "{} must have length 16, got: {}",
+                    name,
+                    bytes.len(),
+                ));
+            }
+
+            let mut out = [0u8; 16];
+            out.copy_from_slice(&bytes);
+            Ok(Some(out))
+        }
+    }
+}
+
+/// Calculates a blake2b hash of `message` in the same way as libsodium's
+/// ``crypto_generichash``.
+///
+/// Parameters
+/// ----------
+/// message : bytes
+///     The message to be hashed.
+/// outlen : int
+///     The number of bytes in the output, between 16 and 64.
+/// key : bytes, optional
+///     A key of between 16 and 64 bytes.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `outlen` bytes representing the hash of `message`.
+#[pyfunction]
+#[pyo3(
+    signature = (message, outlen = generichash::BYTES, key = None),
+    text_signature = "(message, outlen=32, key=None)"
+)]
+fn crypto_generichash(
+    py: Python,
+    message: Vec<u8>,
+    outlen: usize,
+    key: Option<SecretBytes>,
+) -> PyResult<Py<PyAny>> {
+    let result = generichash::crypto_generichash(outlen, &message, &key.unwrap_or_default());
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(PyBytes::new(py, &ok).into()),
+    }
+}
+
+fn _crypto_generichash_blake2b_salt_personal(
+    outlen: usize,
+    message: Vec<u8>,
+    key: Option<SecretBytes>,
+    salt: Option<Vec<u8>>,
+    personal: Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    let salt = checked_salt_personal("salt", salt)?;
+    let personal = checked_salt_personal("personal", personal)?;
+
+    generichash::crypto_generichash_blake2b_salt_personal(
+        outlen,
+        &message,
+        &key.unwrap_or_default(),
+        salt.as_ref(),
+        personal.as_ref(),
+    )
+}
+
+/// Calculates a blake2b hash of `message` in the same way as libsodium's
+/// ``crypto_generichash_blake2b_salt_personal``.
+///
+/// Parameters
+/// ----------
+/// message : bytes
+///     The message to be hashed.
+/// outlen : int
+///     The number of bytes in the output, between 16 and 64.
+/// key : bytes, optional
+///     A key of between 16 and 64 bytes.
+/// salt : bytes, optional
+///     A salt of exactly 16 bytes.  Defaults to all zeros.
+/// personal : bytes, optional
+///     A personalization string of exactly 16 bytes.  Defaults to all zeros.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `outlen` bytes representing the hash of `message`.
+#[pyfunction]
+#[pyo3(
+    signature = (message, outlen = generichash::BYTES, key = None, salt = None, personal = None),
+    text_signature = "(message, outlen=32, key=None, salt=None, personal=None)"
+)]
+fn crypto_generichash_blake2b_salt_personal(
+    py: Python,
+    message: Vec<u8>,
+    outlen: usize,
+    key: Option<SecretBytes>,
+    salt: Option<Vec<u8>>,
+    personal: Option<Vec<u8>>,
+) -> PyResult<Py<PyAny>> {
+    let result = _crypto_generichash_blake2b_salt_personal(outlen, message, key, salt, personal);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(PyBytes::new(py, &ok).into()),
+    }
+}
+
+/// The multi-part hashing state used by ``crypto_generichash_init``,
+/// ``crypto_generichash_update`` and ``crypto_generichash_final``.  The state
+/// is wiped from memory when it is finalized or garbage collected.
+#[pyclass]
+struct GenericHashState {
+    state: Option<generichash::State>,
+    updating: bool,
+}
+
+impl GenericHashState {
+    fn checked_state(&mut self) -> PyResult<&mut generichash::State> {
+        if self.updating {
+            return Err(PyValueError::new_err(
+                "hashing state is being updated on a worker thread",
+            ));
+        }
+
+        match self.state.as_mut() {
+            None => Err(PyValueError::new_err(
+                "hashing state has already been finalized",
+            )),
+            Some(state) => Ok(state),
+        }
+    }
+}
+
+#[pymethods]
+impl GenericHashState {
+    /// Returns a copy of the hashing state.  Only the midstate is copied, so
+    /// messages sharing a long prefix can be hashed by absorbing the prefix
+    /// once and copying the state for each suffix.
+    fn copy(&mut self) -> PyResult<GenericHashState> {
+        let state = self.checked_state()?;
+        Ok(GenericHashState {
+            state: Some(state.resume(&state.midstate())),
+            updating: false,
+        })
+    }
+
+    /// Returns the midstate of the hashing state.
+    ///
+    /// Returns
+    /// -------
+    /// out : (List[int], List[int], bytes)
+    ///     The chaining value and offset counters after every block compressed
+    ///     so far, which are the `starting_state` and `offset_counters`
+    ///     arguments of the next ``compress`` call, and the input buffered
+    ///     since then.
+    fn midstate(&mut self, py: Python) -> PyResult<(Vec<u64>, Vec<u64>, Py<PyAny>)> {
+        let midstate = self.checked_state()?.midstate();
+        Ok((
+            midstate.starting_state().to_vec(),
+            midstate.offset_counters().to_vec(),
+            PyBytes::new(py, midstate.buffered()).into(),
+        ))
+    }
+
+    /// Returns the hashing state serialized as bytes, so that it can be
+    /// pickled and restored on another machine.
+    fn __getstate__(&mut self, py: Python) -> PyResult<Py<PyAny>> {
+        let state = self.checked_state()?;
+        Ok(PyBytes::new(py, &state.to_bytes()).into())
+    }
+
+    /// Replaces the hashing state with one serialized by ``__getstate__``.
+    fn __setstate__(&mut self, state: Vec<u8>) -> PyResult<()> {
+        if self.updating {
+            return Err(PyValueError::new_err(
+                "hashing state is being updated on a worker thread",
+            ));
+        }
+
+        match generichash::State::from_bytes(&state) {
+            Err(msg) => Err(PyValueError::new_err(msg)),
+            Ok(ok) => {
+                self.state = Some(ok);
+                Ok(())
+            }
+        }
+    }
+
+    /// Hashing states are pickled by creating a fresh state with
+    /// ``crypto_generichash_init`` and passing it the output of
+    /// ``__getstate__``.
+    fn __reduce__(&mut self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>, Py<PyAny>)> {
+        let state = self.__getstate__(py)?;
+        let init = py.import("blake2b")?.getattr("crypto_generichash_init")?;
+        Ok((init.into(), PyTuple::empty(py).into(), state))
+    }
+}
+
+/// Creates a multi-part hashing state in the same way as libsodium's
+/// ``crypto_generichash_init``.
+///
+/// Parameters
+/// ----------
+/// key : bytes, optional
+///     A key of between 16 and 64 bytes.
+/// outlen : int
+///     The number of bytes in the output, between 16 and 64.
+///
+/// Returns
+/// -------
+/// out : GenericHashState
+///     A hashing state to pass to ``crypto_generichash_update`` and
+///     ``crypto_generichash_final``.
+#[pyfunction]
+#[pyo3(
+    signature = (key = None, outlen = generichash::BYTES),
+    text_signature = "(key=None, outlen=32)"
+)]
+fn crypto_generichash_init(key: Option<SecretBytes>, outlen: usize) -> PyResult<GenericHashState> {
+    let result = generichash::crypto_generichash_init(&key.unwrap_or_default(), outlen);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(GenericHashState {
+            state: Some(ok),
+            updating: false,
+        }),
+    }
+}
+
+/// Absorbs `message` into the multi-part hashing state `state`.
+///
+/// Parameters
+/// ----------
+/// state : GenericHashState
+///     A hashing state created by ``crypto_generichash_init``.
+/// message : bytes
+///     The next part of the message to be hashed.
+#[pyfunction]
+fn crypto_generichash_update(
+    mut state: PyRefMut<GenericHashState>,
+    message: Vec<u8>,
+) -> PyResult<()> {
+    generichash::crypto_generichash_update(state.checked_state()?, &message);
+    Ok(())
+}
+
+/// Finalizes the multi-part hashing state `state` and returns its hash.  The
+/// state may not be used again afterwards.
+///
+/// Parameters
+/// ----------
+/// state : GenericHashState
+///     A hashing state created by ``crypto_generichash_init``.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of bytes representing the hash of all parts of the message.
+#[pyfunction]
+fn crypto_generichash_final(
+    py: Python,
+    mut state: PyRefMut<GenericHashState>,
+) -> PyResult<Py<PyAny>> {
+    state.checked_state()?;
+    let out = generichash::crypto_generichash_final(state.state.take().unwrap());
+
+    Ok(PyBytes::new(py, &out).into())
+}
+
+/// Zero pads the salt or personalization string `value` to 16 bytes, as
+/// ``hashlib.blake2b`` does.
+fn padded(name: &str, value: Option<Vec<u8>>) -> PyResult<[u8; 16]> {
+    let value = value.unwrap_or_default();
+    if value.len() > 16 {
+        return Err(PyValueError::new_err(format!(
+            "{} must be at most 16 bytes, got: {}",
+            name,
+            value.len()
+        )));
+    }
+
+    let mut out = [0u8; 16];
+    out[..value.len()].copy_from_slice(&value);
+    Ok(out)
+}
+
+/// A blake2b hash object with the same constructor and interface as
+/// ``hashlib.blake2b``, so it can be passed as `digestmod` to ``hmac.new``
+/// and registered with ``hashlib.new`` through ``register``.  Large inputs
+/// are hashed with the GIL released.  The state is wiped from memory when the
+/// object is garbage collected.
+///
+/// Parameters
+/// ----------
+/// data : bytes, optional
+///     Initial input to pass to ``update``.
+/// digest_size : int
+///     The number of bytes in the digest, between 1 and 64.
+/// key : bytes, optional
+///     A key of at most 64 bytes.
+/// salt : bytes, optional
+///     A salt of at most 16 bytes, zero padded to 16 bytes.
+/// person : bytes, optional
+///     A personalization string of at most 16 bytes, zero padded to 16
+///     bytes.
+/// fanout, depth, leaf_size, node_offset, node_depth, inner_size, last_node
+///     The tree hashing parameters, which default to those of sequential
+///     hashing.
+/// usedforsecurity : bool
+///     Ignored, accepted for compatibility with ``hashlib``.
+///
+/// Hash objects can be pickled and copied with the ``copy`` module.  The
+/// pickle of a keyed object can hold the key until more than a block of
+/// input has been absorbed, so treat it as secret.
+#[pyclass(module = "blake2b")]
+struct Blake2b {
+    state: hasher::Blake2b,
+}
+
+/// Inputs of at least this many bytes are hashed with the GIL released, the
+/// same threshold as ``hashlib`` uses.
+const DETACH_BYTES: usize = 2048;
+
+impl Blake2b {
+    fn absorb(&mut self, py: Python, data: &[u8]) {
+        if data.len() >= DETACH_BYTES {
+            let state = &mut self.state;
+            py.detach(|| state.update(data));
+        } else {
+            self.state.update(data);
+        }
+    }
+}
+
+#[pymethods]
+impl Blake2b {
+    #[classattr]
+    const SALT_SIZE: usize = hasher::SALTBYTES;
+
+    #[classattr]
+    const PERSON_SIZE: usize = hasher::PERSONALBYTES;
+
+    #[classattr]
+    const MAX_KEY_SIZE: usize = hasher::KEYBYTES;
+
+    #[classattr]
+    const MAX_DIGEST_SIZE: usize = hasher::OUTBYTES;
+
+    #[allow(clippy::too_many_arguments)]
+    #[new]
+    #[pyo3(
+        signature = (
+            data = None, *, digest_size = hasher::OUTBYTES, key = None, salt = None, person = None,
+            fanout = 1, depth = 1, leaf_size = 0, node_offset = 0, node_depth = 0, inner_size = 0,
+            last_node = false, usedforsecurity = true
+        ),
+        text_signature = "(data=None, *, digest_size=64, key=None, salt=None, person=None, \
+                          fanout=1, depth=1, leaf_size=0, node_offset=0, node_depth=0, \
+                          inner_size=0, last_node=False, usedforsecurity=True)"
+    )]
+    fn new(
+        py: Python,
+        data: Option<Vec<u8>>,
+        digest_size: usize,
+        key: Option<SecretBytes>,
+        salt: Option<Vec<u8>>,
+        person: Option<Vec<u8>>,
+        fanout: u8,
+        depth: u8,
+        leaf_size: u32,
+        node_offset: u64,
+        node_depth: u8,
+        inner_size: u8,
+        last_node: bool,
+        usedforsecurity: bool,
+    ) -> PyResult<Self> {
+        let _ = usedforsecurity;
+        if depth == 0 {
+            return Err(PyValueError::new_err("depth must be between 1 and 255"));
+        }
+
+        let params = Params {
+            digest_length: digest_size,
+            key: key.unwrap_or_default().to_vec(),
+            salt: padded("salt", salt)?,
+            personal: padded("person", person)?,
+            fanout,
+            max_depth: depth,
+            leaf_length: leaf_size,
+            node_offset,
+            node_depth,
+            inner_length: inner_size,
+            last_node,
+        };
+        let state = hasher::Blake2b::with_params(&params).map_err(PyValueError::new_err)?;
+
+        let mut out = Blake2b { state };
+        if let Some(data) = data {
+            out.absorb(py, &data);
+        }
+
+        Ok(out)
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        "blake2b"
+    }
+
+    #[getter]
+    fn digest_size(&self) -> usize {
+        self.state.digest_length()
+    }
+
+    #[getter]
+    fn block_size(&self) -> usize {
+        hasher::BLOCKBYTES
+    }
+
+    /// Absorbs `data` into the hashing state.
+    fn update(&mut self, py: Python, data: Vec<u8>) {
+        self.absorb(py, &data);
+    }
+
+    /// Returns the digest of all input passed to ``update`` so far.
+    fn digest(&self, py: Python) -> Py<PyAny> {
+        PyBytes::new(py, &self.state.finalize()).into()
+    }
+
+    /// Returns the digest of all input passed to ``update`` so far as a string
+    /// of hexadecimal digits.
+    fn hexdigest(&self) -> String {
+        self.state
+            .finalize()
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect()
+    }
+
+    /// Returns a copy of the hashing state.
+    fn copy(&self) -> Blake2b {
+        Blake2b {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Returns the hashing state serialized as bytes, so that it can be
+    /// pickled and restored on another machine.
+    fn __getstate__(&self, py: Python) -> Py<PyAny> {
+        secret_bytes(py, self.state.to_bytes())
+    }
+
+    /// Replaces the hashing state with one serialized by ``__getstate__``.
+    fn __setstate__(&mut self, state: SecretBytes) -> PyResult<()> {
+        self.state = hasher::Blake2b::from_bytes(&state).map_err(PyValueError::new_err)?;
+
+        Ok(())
+    }
+
+    /// Hash objects are pickled by creating a fresh one and passing it the
+    /// output of ``__getstate__``.
+    fn __reduce__(slf: &Bound<Self>) -> (Py<PyAny>, Py<PyAny>, Py<PyAny>) {
+        let py = slf.py();
+        let state = slf.borrow().__getstate__(py);
+        (slf.get_type().into(), PyTuple::empty(py).into(), state)
+    }
+}
+
+/// The number of calls per worker thread that can wait in the queue of the worker pool.
+const QUEUE_PER_WORKER: usize = 64;
+
+/// The worker pool shared by the ``submit_*`` functions, started on first use.
+static POOL: Mutex<Option<ThreadPool>> = Mutex::new(None);
+
+/// Queue `job` on the worker pool.  Once it has run, `callback` is called on the worker thread
+/// with the GIL held as ``callback(result, error)``, where exactly one of the two is ``None`` and
+/// `error` is the exception the call would have raised.
+fn submit<F, T>(job: F, callback: Py<PyAny>) -> PyResult<()>
+where
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+    T: FnOnce(Python) -> Py<PyAny>,
+{
+    let mut pool = POOL.lock().unwrap();
+    if pool.is_none() {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let queue_size = workers * QUEUE_PER_WORKER;
+        *pool = Some(ThreadPool::new(workers, queue_size).map_err(PyValueError::new_err)?);
+    }
+
+    let result = pool.as_ref().unwrap().execute(move || {
+        let result = job();
+
+        Python::attach(|py| {
+            let args = match result {
+                Ok(ok) => (Some(ok(py)), None),
+                Err(err) => (None, Some(err.into_value(py))),
+            };
+            if let Err(err) = callback.call1(py, args) {
+                err.print(py);
+            }
+        });
+    });
+
+    result.map_err(PyRuntimeError::new_err)
+}
+
+/// Sets the number of worker threads used by the ``*_async`` functions, which
+/// bounds how many compression function calls they run at once.  Up to 64
+/// calls per worker can wait in the queue; submitting more raises
+/// ``RuntimeError``.  Calls that are already queued still complete, and this
+/// waits for them.  Defaults to the number of CPUs.
+///
+/// Parameters
+/// ----------
+/// workers : int
+///     The number of worker threads, at least 1.
+#[pyfunction]
+fn set_async_workers(py: Python, workers: usize) -> PyResult<()> {
+    let pool = ThreadPool::new(workers, workers * QUEUE_PER_WORKER);
+    let old = POOL
+        .lock()
+        .unwrap()
+        .replace(pool.map_err(PyValueError::new_err)?);
+    // The queued calls need the GIL to run their callbacks, so release it while waiting for them
+    py.detach(|| drop(old));
+
+    Ok(())
+}
+
+/// Returns the number of worker threads used by the ``*_async`` functions.
+#[pyfunction]
+fn get_async_workers() -> usize {
+    match POOL.lock().unwrap().as_ref() {
+        Some(pool) => pool.workers(),
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+}
+
+/// Runs ``decode_and_compress(input, max_rounds, time_budget)`` on the worker
+/// pool, then calls ``callback(result, error)`` from the worker thread with
+/// either the result or the exception.  Input that can't be decoded or is over
+/// the limits raises an exception at once instead.  The time budget starts
+/// when a worker picks the call up.  This is the building block of
+/// ``decode_and_compress_async``.
+#[pyfunction]
+#[pyo3(signature = (input, callback, max_rounds = None, time_budget = None))]
+fn submit_decode_and_compress(
+    input: Vec<u8>,
+    callback: Py<PyAny>,
+    max_rounds: Option<usize>,
+    time_budget: Option<f64>,
+) -> PyResult<()> {
+    let limits = call_limits(max_rounds, time_budget)?;
+    let args = checked_decode(&input, &limits)?;
+
+    submit(move || compress_job(&limits, &args), callback)
+}
+
+/// Runs ``compress`` on the worker pool, then calls ``callback(result, error)``
+/// from the worker thread with either the result or the exception.  Vectors
+/// of the wrong length or a number of rounds over the limits raise an
+/// exception at once instead.  The time budget starts when a worker picks the
+/// call up.  This is the building block of ``compress_async``.
+#[allow(clippy::too_many_arguments)]
+#[pyfunction]
+#[pyo3(signature = (
+    rounds, starting_state, block, offset_counters, final_block_flag, callback, max_rounds = None, time_budget = None
+))]
+fn submit_compress(
+    rounds: usize,
+    starting_state: Vec<u64>,
+    block: Vec<u64>,
+    offset_counters: Vec<u64>,
+    final_block_flag: bool,
+    callback: Py<PyAny>,
+    max_rounds: Option<usize>,
+    time_budget: Option<f64>,
+) -> PyResult<()> {
+    let limits = call_limits(max_rounds, time_budget)?;
+    let args = compress_args(
+        rounds,
+        &starting_state,
+        &block,
+        &offset_counters,
+        final_block_flag,
+    )?;
+    check_limits(&limits, rounds)?;
+
+    submit(move || compress_job(&limits, &args), callback)
+}
+
+/// Run the compression function for one of the ``submit_*`` functions.
+fn compress_job(
+    limits: &limits::Limits,
+    args: &blake2b::CompressArgs,
+) -> PyResult<impl FnOnce(Python) -> Py<PyAny>> {
+    let result = limits.compress(args).map_err(limit_exceeded)?;
+    Ok(move |py: Python| PyBytes::new(py, &result).into())
+}
+
+/// Absorbs `message` into the multi-part hashing state `state` on the worker
+/// pool, then calls ``callback(None, None)`` from the worker thread.  The
+/// state can't be used until then.  This is the building block of
+/// ``crypto_generichash_update_async``.
+#[pyfunction]
+fn submit_generichash_update(
+    state: &Bound<GenericHashState>,
+    message: Vec<u8>,
+    callback: Py<PyAny>,
+) -> PyResult<()> {
+    let mut cell = state.borrow_mut();
+    let mut inner = cell.checked_state()?.clone();
+    let state = state.clone().unbind();
+
+    submit(
+        move || {
+            generichash::crypto_generichash_update(&mut inner, &message);
+            Ok(move |py: Python| {
+                let mut cell = state.borrow_mut(py);
+                cell.state = Some(inner);
+                cell.updating = false;
+                py.None()
+            })
+        },
+        callback,
+    )?;
+    // The job can't finish before this since it needs the GIL to hand back the state
+    cell.updating = true;
+
+    Ok(())
+}
+
+/// Calculates the hash of the file at `path` without reading it into memory.
+/// The file is memory mapped and hashed with the GIL released.
+///
+/// Parameters
+/// ----------
+/// path : str, os.PathLike
+///     The file to be hashed.
+/// mode : str
+///     ``"blake2b"`` for sequential blake2b, giving the same hash as streaming
+///     the file, ``"blake2bp"`` for BLAKE2bp or ``"tree"`` for a two level
+///     blake2b tree with 1 MiB leaves.  BLAKE2bp and tree hashes are
+///     calculated on several threads.
+/// digest_size : int
+///     The number of bytes in the output, between 1 and 64.
+/// key : bytes, optional
+///     A key of at most 64 bytes.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `digest_size` bytes representing the hash of the file.
+#[pyfunction]
+#[pyo3(
+    signature = (path, mode = "blake2b", digest_size = hasher::OUTBYTES, key = None),
+    text_signature = "(path, mode=\"blake2b\", digest_size=64, key=None)"
+)]
+fn hash_file(
+    py: Python,
+    path: PathBuf,
+    mode: &str,
+    digest_size: usize,
+    key: Option<SecretBytes>,
+) -> PyResult<Py<PyAny>> {
+    let mode = match mode.parse() {
+        Err(msg) => return Err(PyValueError::new_err(msg)),
+        Ok(mode) => mode,
+    };
+    let params = Params {
+        digest_length: digest_size,
+        key: key.unwrap_or_default().to_vec(),
+        ..Params::default()
+    };
+
+    let result = py.detach(|| file::hash_file(&path, mode, &params));
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(PyBytes::new(py, &ok).into()),
+    }
+}
+
+/// Extracts a pseudorandom key from the input keying material `ikm` using
+/// blake2b's native keyed mode with `salt` as the key.
+///
+/// Parameters
+/// ----------
+/// salt : bytes
+///     A salt value.  An empty salt is replaced by 64 zero bytes and a salt
+///     longer than 64 bytes is hashed down to 64 bytes.
+/// ikm : bytes
+///     The input keying material.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A 64 byte pseudorandom key.
+#[pyfunction]
+fn kdf_extract(py: Python, salt: Vec<u8>, ikm: SecretBytes) -> Py<PyAny> {
+    secret_bytes(py, kdf::extract(&salt, &ikm))
+}
+
+/// Expands the pseudorandom key `prk` into `length` bytes of output keying
+/// material using blake2b's native keyed mode with `prk` as the key.
+///
+/// Parameters
+/// ----------
+/// prk : bytes
+///     A pseudorandom key of at most 64 bytes, usually the output of
+///     ``kdf_extract``.
+/// info : bytes
+///     Context and application specific information.
+/// length : int
+///     The number of bytes of output keying material, at most 16320.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `length` bytes of output keying material.
+#[pyfunction]
+fn kdf_expand(py: Python, prk: SecretBytes, info: Vec<u8>, length: usize) -> PyResult<Py<PyAny>> {
+    let result = kdf::expand(&prk, &info, length);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(secret_bytes(py, ok)),
+    }
+}
+
+/// Derives `length` bytes of output keying material from `ikm` with the
+/// ``kdf_extract`` and ``kdf_expand`` steps.
+///
+/// Parameters
+/// ----------
+/// ikm : bytes
+///     The input keying material.
+/// salt : bytes, optional
+///     A salt value.
+/// info : bytes, optional
+///     Context and application specific information.
+/// length : int
+///     The number of bytes of output keying material, at most 16320.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `length` bytes of output keying material.
+#[pyfunction]
+#[pyo3(
+    name = "kdf",
+    signature = (ikm, salt = None, info = None, length = kdf::PRKBYTES),
+    text_signature = "(ikm, salt=None, info=None, length=64)"
+)]
+fn derive_key(
+    py: Python,
+    ikm: SecretBytes,
+    salt: Option<Vec<u8>>,
+    info: Option<Vec<u8>>,
+    length: usize,
+) -> PyResult<Py<PyAny>> {
+    let result = kdf::derive(
+        &ikm,
+        &salt.unwrap_or_default(),
+        &info.unwrap_or_default(),
+        length,
+    );
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(secret_bytes(py, ok)),
+    }
+}
+
+/// Extracts a pseudorandom key from the input keying material `ikm` with
+/// HMAC-BLAKE2b-512 as described in RFC 5869.
+///
+/// Parameters
+/// ----------
+/// salt : bytes
+///     A salt value.  An empty salt is replaced by 64 zero bytes.
+/// ikm : bytes
+///     The input keying material.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A 64 byte pseudorandom key.
+#[pyfunction]
+fn hkdf_extract(py: Python, salt: Vec<u8>, ikm: SecretBytes) -> Py<PyAny> {
+    secret_bytes(py, kdf::hkdf_extract(&salt, &ikm))
+}
+
+/// Expands the pseudorandom key `prk` into `length` bytes of output keying
+/// material with HMAC-BLAKE2b-512 as described in RFC 5869.
+///
+/// Parameters
+/// ----------
+/// prk : bytes
+///     A pseudorandom key, usually the output of ``hkdf_extract``.
+/// info : bytes
+///     Context and application specific information.
+/// length : int
+///     The number of bytes of output keying material, at most 16320.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `length` bytes of output keying material.
+#[pyfunction]
+fn hkdf_expand(py: Python, prk: SecretBytes, info: Vec<u8>, length: usize) -> PyResult<Py<PyAny>> {
+    let result = kdf::hkdf_expand(&prk, &info, length);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(secret_bytes(py, ok)),
+    }
+}
+
+/// Derives `length` bytes of output keying material from `ikm` with
+/// HKDF-BLAKE2b-512 as described in RFC 5869.
+///
+/// Parameters
+/// ----------
+/// ikm : bytes
+///     The input keying material.
+/// salt : bytes, optional
+///     A salt value.
+/// info : bytes, optional
+///     Context and application specific information.
+/// length : int
+///     The number of bytes of output keying material, at most 16320.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `length` bytes of output keying material.
+#[pyfunction]
+#[pyo3(
+    signature = (ikm, salt = None, info = None, length = kdf::PRKBYTES),
+    text_signature = "(ikm, salt=None, info=None, length=64)"
+)]
+fn hkdf(
+    py: Python,
+    ikm: SecretBytes,
+    salt: Option<Vec<u8>>,
+    info: Option<Vec<u8>>,
+    length: usize,
+) -> PyResult<Py<PyAny>> {
+    let result = kdf::hkdf(
+        &ikm,
+        &salt.unwrap_or_default(),
+        &info.unwrap_or_default(),
+        length,
+    );
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(secret_bytes(py, ok)),
+    }
+}
+
+/// Derives a key from `password` and `salt` with PBKDF2-HMAC-BLAKE2b-512 as
+/// described in RFC 8018.  The result is the same as
+/// ``hashlib.pbkdf2_hmac("blake2b512", password, salt, iterations, dklen)``,
+/// and the GIL is released while it is calculated.
+///
+/// Parameters
+/// ----------
+/// password : bytes
+///     The password.
+/// salt : bytes
+///     A salt value.
+/// iterations : int
+///     The number of iterations of HMAC-BLAKE2b-512 per 64 bytes of output,
+///     at least 1.
+/// dklen : int, optional
+///     The number of bytes in the derived key.  Defaults to 64.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of `dklen` bytes representing the derived key.
+#[pyfunction]
+#[pyo3(signature = (password, salt, iterations, dklen = None))]
+fn pbkdf2_hmac_blake2b(
+    py: Python,
+    password: SecretBytes,
+    salt: Vec<u8>,
+    iterations: u32,
+    dklen: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    let dklen = dklen.unwrap_or(hmac::OUTBYTES);
+    let result = py.detach(|| kdf::pbkdf2_hmac_blake2b(&password, &salt, iterations, dklen));
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(secret_bytes(py, ok)),
+    }
+}
+
+/// An HMAC-BLAKE2b-512 object with the same interface as the objects returned
+/// by ``hmac.new(key, msg, hashlib.blake2b)``.  The keyed state is wiped from
+/// memory when the object is garbage collected.
+///
+/// Parameters
+/// ----------
+/// key : bytes
+///     The secret key.
+/// msg : bytes, optional
+///     Initial input to pass to ``update``.
+#[pyclass(name = "HMAC")]
+struct Hmac {
+    state: hmac::Hmac,
+}
+
+#[pymethods]
+impl Hmac {
+    #[new]
+    #[pyo3(signature = (key, msg = None))]
+    fn new(key: SecretBytes, msg: Option<Vec<u8>>) -> Self {
+        let mut state = hmac::Hmac::new(&key);
+        if let Some(msg) = msg {
+            state.update(&msg);
+        }
+
+        Hmac { state }
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        "hmac-blake2b"
+    }
+
+    #[getter]
+    fn digest_size(&self) -> usize {
+        hmac::OUTBYTES
+    }
+
+    #[getter]
+    fn block_size(&self) -> usize {
+        hmac::BLOCKBYTES
+    }
+
+    /// Absorbs `msg` into the HMAC state.
+    fn update(&mut self, msg: Vec<u8>) {
+        self.state.update(&msg);
+    }
+
+    /// Returns the tag of all input passed to ``update`` so far.
+    fn digest(&self, py: Python) -> Py<PyAny> {
+        PyBytes::new(py, &self.state.finalize()).into()
+    }
+
+    /// Returns the tag of all input passed to ``update`` so far as a string
+    /// of hexadecimal digits.
+    fn hexdigest(&self) -> String {
+        self.state
+            .finalize()
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect()
+    }
+
+    /// Returns a copy of the HMAC state.
+    fn copy(&self) -> Hmac {
+        Hmac {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Checks in constant time whether `tag` is the tag of all input passed to
+    /// ``update`` so far.
+    fn verify(&self, tag: Vec<u8>) -> bool {
+        self.state.verify(&tag)
+    }
+}
+
+/// Calculates the HMAC-BLAKE2b-512 tag of `msg` keyed with `key`.  The result
+/// is the same as ``hmac.new(key, msg, hashlib.blake2b).digest()``.
+///
+/// Parameters
+/// ----------
+/// key : bytes
+///     The secret key.
+/// msg : bytes
+///     The message to be authenticated.
+///
+/// Returns
+/// -------
+/// out : bytes
+///     A vector of 64 bytes representing the tag of `msg`.
+#[pyfunction]
+fn hmac_blake2b(py: Python, key: SecretBytes, msg: Vec<u8>) -> Py<PyAny> {
+    PyBytes::new(py, &hmac::hmac(&key, &msg)).into()
+}
+
+/// Compares the byte sequences `a` and `b` in constant time.  Only their
+/// lengths may affect the running time.
+///
+/// Parameters
+/// ----------
+/// a : bytes
+///     A digest or tag.
+/// b : bytes
+///     A digest or tag.
+///
+/// Returns
+/// -------
+/// out : bool
+///     ``True`` if `a` and `b` are equal.
+#[pyfunction]
+fn compare_digest(a: Vec<u8>, b: Vec<u8>) -> bool {
+    verify::compare_digest(&a, &b)
+}
+
+/// Checks in constant time whether `expected_tag` is the keyed blake2b tag of
+/// `message` under `key`.  A tag whose length differs from `digest_size`
+/// fails to verify.
+///
+/// Parameters
+/// ----------
+/// expected_tag : bytes
+///     The tag to check.
+/// key : bytes
+///     A key of at most 64 bytes.
+/// message : bytes
+///     The message to be authenticated.
+/// digest_size : int
+///     The number of bytes in the tag, between 1 and 64.
+///
+/// Returns
+/// -------
+/// out : bool
+///     ``True`` if `expected_tag` is the tag of `message`.
+#[pyfunction]
+#[pyo3(
+    name = "verify",
+    signature = (expected_tag, key, message, digest_size = hasher::OUTBYTES),
+    text_signature = "(expected_tag, key, message, digest_size=64)"
+)]
+fn verify_tag(
+    expected_tag: Vec<u8>,
+    key: SecretBytes,
+    message: Vec<u8>,
+    digest_size: usize,
+) -> PyResult<bool> {
+    let result = verify::verify(&expected_tag, &key, &message, digest_size);
+
+    match result {
+        Err(msg) => Err(PyValueError::new_err(msg)),
+        Ok(ok) => Ok(ok),
+    }
+}
+
+/// Functions for calculating blake2b hashes.
+#[pymodule]
+#[pyo3(name = "blake2b")]
+fn init_module(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(decode_parameters, m)?)?;
+    m.add_class::<CompressArgs>()?;
+    m.add_function(wrap_pyfunction!(compress, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_and_compress, m)?)?;
+    m.add("LimitExceeded", m.py().get_type::<LimitExceeded>())?;
+    m.add_function(wrap_pyfunction!(set_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(get_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(compress_trace, m)?)?;
+    m.add_class::<CompressTrace>()?;
+    m.add_function(wrap_pyfunction!(mix, m)?)?;
+    m.add_function(wrap_pyfunction!(round_function, m)?)?;
+    m.add_function(wrap_pyfunction!(permute, m)?)?;
+    m.add_function(wrap_pyfunction!(crypto_generichash, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        crypto_generichash_blake2b_salt_personal,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(crypto_generichash_init, m)?)?;
+    m.add_function(wrap_pyfunction!(crypto_generichash_update, m)?)?;
+    m.add_function(wrap_pyfunction!(crypto_generichash_final, m)?)?;
+    m.add_class::<GenericHashState>()?;
+    m.add_class::<Blake2b>()?;
+    m.add_function(wrap_pyfunction!(hash_file, m)?)?;
+    m.add_function(wrap_pyfunction!(set_async_workers, m)?)?;
+    m.add_function(wrap_pyfunction!(get_async_workers, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_decode_and_compress, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_compress, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_generichash_update, m)?)?;
+    m.add_function(wrap_pyfunction!(kdf_extract, m)?)?;
+    m.add_function(wrap_pyfunction!(kdf_expand, m)?)?;
+    m.add_function(wrap_pyfunction!(derive_key, m)?)?;
+    m.add_function(wrap_pyfunction!(hkdf_extract, m)?)?;
+    m.add_function(wrap_pyfunction!(hkdf_expand, m)?)?;
+    m.add_function(wrap_pyfunction!(hkdf, m)?)?;
+    m.add_function(wrap_pyfunction!(pbkdf2_hmac_blake2b, m)?)?;
+    m.add_function(wrap_pyfunction!(hmac_blake2b, m)?)?;
+    m.add_class::<Hmac>()?;
+    m.add_function(wrap_pyfunction!(compare_digest, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_tag, m)?)?;
+
+    Ok(())
+}
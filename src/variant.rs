@@ -0,0 +1,299 @@
+//! Variants of the blake2b compression function with other rotation constants, IV and message
+//! schedule, for studying modified and reduced-round versions of blake2b.
+//!
+//! The standard compression function `blake2b::F` keeps its own code path with the constants
+//! compiled in; nothing in this module is used to compute real blake2b hashes.
+
+use crate::blake2b::{check_lengths, IV, SIGMA};
+use crate::zeroize::zeroize;
+
+/// The rotation distances used by the standard `G`, in order.
+pub const ROTATIONS: [u32; 4] = [32, 24, 16, 63];
+
+/// The constants of a compression function variant.  Implement this on a unit struct returning
+/// fixed values to have them compiled into `compress`, or use `Variant` to choose them at runtime.
+pub trait Constants {
+    /// The distances of the four rotations in `G`, in order.
+    fn rotations(&self) -> [u32; 4];
+
+    /// The initialization vector, which fills the second half of the working vector.
+    fn iv(&self) -> [u64; 8];
+
+    /// The message schedule.  Round `r` reads the message words in the order given by row
+    /// `r % sigma.len()`.  Rows need not be permutations.
+    fn sigma(&self) -> &[[usize; 16]];
+}
+
+/// The standard blake2b constants.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake2b;
+
+impl Constants for Blake2b {
+    #[inline]
+    fn rotations(&self) -> [u32; 4] {
+        ROTATIONS
+    }
+
+    #[inline]
+    fn iv(&self) -> [u64; 8] {
+        IV
+    }
+
+    #[inline]
+    fn sigma(&self) -> &[[usize; 16]] {
+        &SIGMA
+    }
+}
+
+/// Compression function constants chosen at runtime.  The default is the standard blake2b
+/// constants.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variant {
+    pub rotations: [u32; 4],
+    pub iv: [u64; 8],
+    pub sigma: Vec<[usize; 16]>,
+}
+
+impl Default for Variant {
+    fn default() -> Self {
+        Variant {
+            rotations: ROTATIONS,
+            iv: IV,
+            sigma: SIGMA.to_vec(),
+        }
+    }
+}
+
+impl Constants for Variant {
+    fn rotations(&self) -> [u32; 4] {
+        self.rotations
+    }
+
+    fn iv(&self) -> [u64; 8] {
+        self.iv
+    }
+
+    fn sigma(&self) -> &[[usize; 16]] {
+        &self.sigma
+    }
+}
+
+fn check_constants<C: Constants + ?Sized>(constants: &C) -> Result<(), String> {
+    for &rotation in constants.rotations().iter() {
+        if rotation >= 64 {
+            return Err(format!(
+                "rotation distances must be less than 64, got: {}",
+                rotation
+            ));
+        }
+    }
+
+    let sigma = constants.sigma();
+    if sigma.is_empty() {
+        return Err("message schedule must have at least one row".to_string());
+    }
+    for row in sigma {
+        if let Some(index) = row.iter().find(|&&index| index >= 16) {
+            return Err(format!(
+                "message schedule indices must be less than 16, got: {}",
+                index
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case, clippy::too_many_arguments)]
+#[inline]
+fn G(v: &mut [u64; 16], rot: [u32; 4], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(rot[0]);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(rot[1]);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(rot[2]);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(rot[3]);
+}
+
+/// The blake2b compression function `F` with the rotation distances, IV and message schedule
+/// given by `constants`.  With `Blake2b` or `Variant::default()` this gives the same output as
+/// `F`.
+///
+/// See here: https://tools.ietf.org/html/rfc7693#section-3.2
+pub fn compress<C: Constants + ?Sized>(
+    constants: &C,
+    rounds: usize,
+    starting_state: &[u64],
+    block: &[u64],
+    offset_counters: &[u64],
+    final_block_flag: bool,
+) -> Result<[u8; 64], String> {
+    check_lengths(starting_state, block, offset_counters)?;
+    check_constants(constants)?;
+
+    let h = starting_state;
+    let m = block;
+    let t = offset_counters;
+    let iv = constants.iv();
+    let rot = constants.rotations();
+    let sigma = constants.sigma();
+
+    let mut v = [
+        h[0],
+        h[1],
+        h[2],
+        h[3],
+        h[4],
+        h[5],
+        h[6],
+        h[7],
+        iv[0],
+        iv[1],
+        iv[2],
+        iv[3],
+        iv[4] ^ t[0],
+        iv[5] ^ t[1],
+        if final_block_flag { !iv[6] } else { iv[6] },
+        iv[7],
+    ];
+
+    for r in 0..rounds {
+        let s = &sigma[r % sigma.len()];
+
+        G(&mut v, rot, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        G(&mut v, rot, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        G(&mut v, rot, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        G(&mut v, rot, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+
+        G(&mut v, rot, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        G(&mut v, rot, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        G(&mut v, rot, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        G(&mut v, rot, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    let mut result = [0u8; 64];
+    for (i, chunk) in result.chunks_exact_mut(8).enumerate() {
+        chunk.copy_from_slice(&(h[i] ^ v[i] ^ v[i + 8]).to_le_bytes());
+    }
+
+    zeroize(&mut v);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::blake2b::{decode_parameters, CompressArgs, F, SIGMA_LEN};
+
+    use std::convert::TryInto;
+
+    const EIP_152_VEC_5: &str = "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001";
+
+    fn args() -> CompressArgs {
+        decode_parameters(&hex::decode(EIP_152_VEC_5).unwrap()).unwrap()
+    }
+
+    /// A compile-time variant with the standard message schedule written out twice over
+    struct DoubledSigma([[usize; 16]; 2 * SIGMA_LEN]);
+
+    impl Constants for DoubledSigma {
+        fn rotations(&self) -> [u32; 4] {
+            ROTATIONS
+        }
+
+        fn iv(&self) -> [u64; 8] {
+            IV
+        }
+
+        fn sigma(&self) -> &[[usize; 16]] {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_standard_constants_match_f() {
+        let CompressArgs { h, m, t, f, .. } = args();
+
+        for rounds in [0, 1, 12, 25].iter().cloned() {
+            let expected = F(rounds, &h, &m, &t, f);
+
+            assert_eq!(compress(&Blake2b, rounds, &h, &m, &t, f), Ok(expected));
+            assert_eq!(
+                compress(&Variant::default(), rounds, &h, &m, &t, f),
+                Ok(expected)
+            );
+
+            let mut doubled = [[0; 16]; 2 * SIGMA_LEN];
+            doubled[..SIGMA_LEN].copy_from_slice(&SIGMA);
+            doubled[SIGMA_LEN..].copy_from_slice(&SIGMA);
+            assert_eq!(
+                compress(&DoubledSigma(doubled), rounds, &h, &m, &t, f),
+                Ok(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_iv_without_rounds() {
+        // With no rounds the output is the second half of the initial working vector
+        let variant = Variant {
+            iv: [1, 2, 3, 4, 5, 6, 7, 8],
+            ..Variant::default()
+        };
+        let out = compress(&variant, 0, &[0; 8], &[0; 16], &[9, 10], true).unwrap();
+
+        let words: Vec<u64> = out
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        assert_eq!(words, vec![1, 2, 3, 4, 5 ^ 9, 6 ^ 10, !7, 8]);
+    }
+
+    #[test]
+    fn test_modified_constants_change_output() {
+        let CompressArgs { rounds, h, m, t, f } = args();
+        let expected = F(rounds, &h, &m, &t, f);
+
+        let rotations = Variant {
+            rotations: [32, 24, 16, 62],
+            ..Variant::default()
+        };
+        let schedule = Variant {
+            sigma: vec![SIGMA[0]],
+            ..Variant::default()
+        };
+        let iv = Variant {
+            iv: [0; 8],
+            ..Variant::default()
+        };
+
+        for variant in [rotations, schedule, iv].iter() {
+            assert_ne!(compress(variant, rounds, &h, &m, &t, f).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_compress_error() {
+        let bad_rotation = Variant {
+            rotations: [32, 24, 16, 64],
+            ..Variant::default()
+        };
+        let empty_schedule = Variant {
+            sigma: vec![],
+            ..Variant::default()
+        };
+        let bad_index = Variant {
+            sigma: vec![[16; 16]],
+            ..Variant::default()
+        };
+
+        for variant in [bad_rotation, empty_schedule, bad_index].iter() {
+            assert!(compress(variant, 12, &[0; 8], &[0; 16], &[0; 2], false).is_err());
+        }
+        assert!(compress(&Blake2b, 12, &[0; 7], &[0; 16], &[0; 2], false).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Constant-time comparison of digests and MAC tags.
+
+use crate::hasher::{self, Params};
+
+/// Return the bitwise OR of the differences between each pair of bytes in `a` and `b`.  Every
+/// pair is visited regardless of earlier differences.
+#[inline(never)]
+fn accumulate_difference(a: &[u8], b: &[u8]) -> u8 {
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+
+    diff
+}
+
+/// Compare the byte sequences `a` and `b` without branching on their contents.  Only their
+/// lengths, which are not secret, may affect the running time.
+pub fn compare_digest(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = accumulate_difference(a, b);
+
+    // A volatile read keeps the optimizer from reasoning about the accumulated difference and
+    // replacing the loop above with an early exit.
+    unsafe { std::ptr::read_volatile(&diff) == 0 }
+}
+
+/// Check in constant time whether `expected_tag` is the keyed blake2b tag of `message` under
+/// `key` with a digest length of `digest_length` bytes.  The digest length is never taken from
+/// `expected_tag`, since a forger could then send a one byte tag, so a tag of any other length
+/// fails to verify.
+pub fn verify(
+    expected_tag: &[u8],
+    key: &[u8],
+    message: &[u8],
+    digest_length: usize,
+) -> Result<bool, String> {
+    let params = Params {
+        digest_length,
+        key: key.to_vec(),
+        ..Params::default()
+    };
+    let tag = hasher::hash(&params, message)?;
+
+    Ok(compare_digest(&tag, expected_tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_digest() {
+        assert!(compare_digest(b"", b""));
+        assert!(compare_digest(b"abc", b"abc"));
+        assert!(!compare_digest(b"abc", b"abd"));
+        assert!(!compare_digest(b"abc", b"ab"));
+    }
+
+    #[test]
+    fn test_compare_digest_does_not_short_circuit() {
+        // Each pair of bytes differs in a distinct bit, so the accumulated difference only has all
+        // of those bits set if every pair was visited after the first mismatch.
+        let a = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let b = [0x00, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80];
+
+        assert_eq!(accumulate_difference(&a, &b), 0xff);
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = b"a secret key of at least 16 bytes";
+        let message = b"message";
+        let params = Params {
+            digest_length: 32,
+            key: key.to_vec(),
+            ..Params::default()
+        };
+        let mut tag = hasher::hash(&params, message).unwrap();
+
+        assert!(verify(&tag, key, message, 32).unwrap());
+        assert!(!verify(&tag, key, b"other message", 32).unwrap());
+
+        tag[0] ^= 1;
+        assert!(!verify(&tag, key, message, 32).unwrap());
+
+        assert!(verify(&tag, key, message, 0).is_err());
+        assert!(verify(&tag, &[0; 65], message, 32).is_err());
+    }
+
+    #[test]
+    fn test_verify_truncated_tag() {
+        let key = b"a secret key of at least 16 bytes";
+        let message = b"message";
+        let tag = hasher::hash(
+            &Params {
+                key: key.to_vec(),
+                ..Params::default()
+            },
+            message,
+        )
+        .unwrap();
+
+        assert!(verify(&tag, key, message, hasher::OUTBYTES).unwrap());
+        for length in 0..tag.len() {
+            assert!(!verify(&tag[..length], key, message, hasher::OUTBYTES).unwrap());
+        }
+    }
+}
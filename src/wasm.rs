@@ -0,0 +1,96 @@
+//! A JavaScript API for the `wasm32-unknown-unknown` target, built with wasm-bindgen.
+//!
+//! Byte sequences are passed in and returned as `Uint8Array`s.  Errors are thrown as JavaScript
+//! `Error`s carrying the same messages as the Python bindings' `ValueError`s.
+
+use wasm_bindgen::prelude::*;
+
+use crate::blake2b;
+use crate::hasher::{self, Params};
+
+fn checked_words(name: &str, input: &[u8], length: usize) -> Result<Vec<u64>, JsError> {
+    if input.len() != length * 8 {
+        return Err(JsError::new(&format!(
+            "{} must have length {} bytes, got: {}",
+            name,
+            length * 8,
+            input.len(),
+        )));
+    }
+
+    Ok(input
+        .chunks(8)
+        .map(|word| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(word);
+            u64::from_le_bytes(bytes)
+        })
+        .collect())
+}
+
+/// Calculate a blake2b hash for the tightly encoded blake2 F precompile input given in the byte
+/// sequence `input`.
+///
+/// See here: https://eips.ethereum.org/EIPS/eip-152#specification
+#[wasm_bindgen(js_name = decodeAndCompress)]
+pub fn decode_and_compress(input: &[u8]) -> Result<Vec<u8>, JsError> {
+    let args = blake2b::decode_parameters(input).map_err(|msg| JsError::new(&msg))?;
+
+    Ok(args.compress().to_vec())
+}
+
+/// Calculate a blake2b hash for the given message block.  The starting state, block and offset
+/// counters are given as the little endian encodings of 8, 16 and 2 64-bit words respectively.
+#[wasm_bindgen]
+pub fn compress(
+    rounds: u32,
+    starting_state: &[u8],
+    block: &[u8],
+    offset_counters: &[u8],
+    final_block_flag: bool,
+) -> Result<Vec<u8>, JsError> {
+    let h = checked_words("starting state", starting_state, 8)?;
+    let m = checked_words("block", block, 16)?;
+    let t = checked_words("offset counters", offset_counters, 2)?;
+
+    Ok(blake2b::F(rounds as usize, &h, &m, &t, final_block_flag).to_vec())
+}
+
+/// A streaming blake2b hasher, exported to JavaScript as `Blake2b`.
+#[wasm_bindgen(js_name = Blake2b)]
+#[derive(Clone)]
+pub struct Hasher {
+    state: hasher::Blake2b,
+}
+
+#[wasm_bindgen(js_class = Blake2b)]
+impl Hasher {
+    /// Create a hasher producing `digestLength` byte digests, 64 by default, optionally keyed with
+    /// `key`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(digest_length: Option<usize>, key: Option<Vec<u8>>) -> Result<Hasher, JsError> {
+        let params = Params {
+            digest_length: digest_length.unwrap_or(hasher::OUTBYTES),
+            key: key.unwrap_or_default(),
+            ..Params::default()
+        };
+        let state = hasher::Blake2b::with_params(&params).map_err(|msg| JsError::new(&msg))?;
+
+        Ok(Hasher { state })
+    }
+
+    /// Absorb the bytes in `input` into the hashing state.
+    pub fn update(&mut self, input: &[u8]) {
+        self.state.update(input);
+    }
+
+    /// Return the digest of all input absorbed so far.  More input may be absorbed afterwards.
+    pub fn digest(&self) -> Vec<u8> {
+        self.state.finalize()
+    }
+
+    /// Return an independent copy of the hashing state.
+    pub fn copy(&self) -> Hasher {
+        self.clone()
+    }
+}
@@ -0,0 +1,29 @@
+//! Wiping of secret data from memory.
+
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Overwrite every element of `data` with its default value.  Volatile writes are used so that
+/// the optimizer can't remove the stores even though the data is never read again.
+pub fn zeroize<T: Copy + Default>(data: &mut [T]) {
+    for x in data.iter_mut() {
+        unsafe { std::ptr::write_volatile(x, T::default()) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut bytes = [0xffu8; 128];
+        let mut words = vec![u64::MAX; 16];
+
+        zeroize(&mut bytes);
+        zeroize(&mut words);
+
+        assert!(bytes.iter().all(|x| *x == 0));
+        assert!(words.iter().all(|x| *x == 0));
+    }
+}
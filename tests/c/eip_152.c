@@ -0,0 +1,230 @@
+/* Checks the C ABI against the test vectors from EIP-152.  Built and run by tests/capi.rs. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "blake2b.h"
+
+struct success_example {
+    const char *input;
+    const char *expected;
+};
+
+struct error_example {
+    const char *input;
+    int32_t expected;
+};
+
+/* EIP-152 test vectors 4 to 7 */
+static const struct success_example SUCCESS_EXAMPLES[] = {
+    {
+        "0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b"
+        "8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000300000000000000000000000000000001",
+        "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b"
+        "9442be0454267ce079217e1319cde05b",
+    },
+    {
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b"
+        "8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000300000000000000000000000000000001",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc95"
+        "18d38aa8dbf1925ab92386edd4009923",
+    },
+    {
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b"
+        "8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000300000000000000000000000000000000",
+        "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d2875298743e7f6d5ea2f2d3e8d226039cd31b"
+        "4e426ac4f2d3d666a610c2116fde4735",
+    },
+    {
+        "0000000148c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b"
+        "8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000300000000000000000000000000000001",
+        "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fba551b3a8333bcdf5f2f7e08993d53923"
+        "de3d64fcc68c034e717b9293fed7a421",
+    },
+};
+
+/* EIP-152 test vectors 0 to 3 */
+static const struct error_example ERROR_EXAMPLES[] = {
+    {
+        "",
+        BLAKE2B_ERR_INPUT_LENGTH,
+    },
+    {
+        "00000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c"
+        "68059b6bbd41fbabd9831f79217e1319cde05b6162630000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "0000000300000000000000000000000000000001",
+        BLAKE2B_ERR_INPUT_LENGTH,
+    },
+    {
+        "000000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e"
+        "2b8c68059b6bbd41fbabd9831f79217e1319cde05b616263000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "00000000000300000000000000000000000000000001",
+        BLAKE2B_ERR_INPUT_LENGTH,
+    },
+    {
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b"
+        "8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+        "000000000300000000000000000000000000000002",
+        BLAKE2B_ERR_FINAL_BLOCK_FLAG,
+    },
+};
+
+static size_t decode_hex(const char *hex, uint8_t *out, size_t out_len) {
+    size_t len = strlen(hex) / 2;
+    unsigned int byte;
+
+    for (size_t i = 0; i < len && i < out_len; i++) {
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+
+    return len;
+}
+
+static void encode_hex(const uint8_t *bytes, size_t len, char *out) {
+    for (size_t i = 0; i < len; i++) {
+        sprintf(out + 2 * i, "%02x", bytes[i]);
+    }
+}
+
+static uint64_t u64_from_le(const uint8_t *bytes) {
+    uint64_t word = 0;
+    for (int i = 7; i >= 0; i--) {
+        word = (word << 8) | bytes[i];
+    }
+
+    return word;
+}
+
+static int check_output(const char *name, size_t i, const uint8_t *out, const char *expected) {
+    char hex[2 * BLAKE2B_F_OUTPUT_BYTES + 1];
+    encode_hex(out, BLAKE2B_F_OUTPUT_BYTES, hex);
+
+    if (strcmp(hex, expected) != 0) {
+        fprintf(stderr, "%s: example %zu: expected %s, got %s\n", name, i, expected, hex);
+        return 1;
+    }
+
+    return 0;
+}
+
+static int test_decode_and_compress(void) {
+    uint8_t input[BLAKE2B_F_INPUT_BYTES + 1];
+    uint8_t out[BLAKE2B_F_OUTPUT_BYTES];
+    int failures = 0;
+
+    for (size_t i = 0; i < sizeof(SUCCESS_EXAMPLES) / sizeof(SUCCESS_EXAMPLES[0]); i++) {
+        size_t len = decode_hex(SUCCESS_EXAMPLES[i].input, input, sizeof(input));
+        int32_t code = blake2b_decode_and_compress(input, len, out);
+
+        if (code != BLAKE2B_OK) {
+            fprintf(stderr, "test_decode_and_compress: example %zu: error %d\n", i, code);
+            failures++;
+        } else {
+            failures += check_output("test_decode_and_compress", i, out,
+                                     SUCCESS_EXAMPLES[i].expected);
+        }
+    }
+
+    for (size_t i = 0; i < sizeof(ERROR_EXAMPLES) / sizeof(ERROR_EXAMPLES[0]); i++) {
+        size_t len = decode_hex(ERROR_EXAMPLES[i].input, input, sizeof(input));
+        int32_t code = blake2b_decode_and_compress(input, len, out);
+
+        if (code != ERROR_EXAMPLES[i].expected) {
+            fprintf(stderr, "test_decode_and_compress: error example %zu: expected %d, got %d\n",
+                    i, ERROR_EXAMPLES[i].expected, code);
+            failures++;
+        }
+    }
+
+    return failures;
+}
+
+static int test_f(void) {
+    uint8_t input[BLAKE2B_F_INPUT_BYTES];
+    uint8_t out[BLAKE2B_F_OUTPUT_BYTES];
+    uint64_t h[8], m[16], t[2];
+    int failures = 0;
+
+    for (size_t i = 0; i < sizeof(SUCCESS_EXAMPLES) / sizeof(SUCCESS_EXAMPLES[0]); i++) {
+        decode_hex(SUCCESS_EXAMPLES[i].input, input, sizeof(input));
+
+        uint32_t rounds = (uint32_t)input[0] << 24 | (uint32_t)input[1] << 16 |
+                          (uint32_t)input[2] << 8 | (uint32_t)input[3];
+        for (size_t j = 0; j < 8; j++) {
+            h[j] = u64_from_le(input + 4 + 8 * j);
+        }
+        for (size_t j = 0; j < 16; j++) {
+            m[j] = u64_from_le(input + 68 + 8 * j);
+        }
+        for (size_t j = 0; j < 2; j++) {
+            t[j] = u64_from_le(input + 196 + 8 * j);
+        }
+
+        int32_t code = blake2b_f(rounds, h, m, t, input[212] == 1, out);
+        if (code != BLAKE2B_OK) {
+            fprintf(stderr, "test_f: example %zu: error %d\n", i, code);
+            failures++;
+        } else {
+            failures += check_output("test_f", i, out, SUCCESS_EXAMPLES[i].expected);
+        }
+    }
+
+    return failures;
+}
+
+static int test_hasher(void) {
+    /* Twelve rounds of F over the padded block "abc" in EIP-152 test vector 5 is the final
+     * compression of blake2b-512("abc") */
+    const char *expected = SUCCESS_EXAMPLES[1].expected;
+    uint8_t out[BLAKE2B_F_OUTPUT_BYTES];
+    int failures = 0;
+
+    blake2b_hasher *hasher = blake2b_hasher_new(sizeof(out), NULL, 0);
+    if (hasher == NULL) {
+        fprintf(stderr, "test_hasher: blake2b_hasher_new failed\n");
+        return 1;
+    }
+    failures += blake2b_hasher_update(hasher, (const uint8_t *)"ab", 2) != BLAKE2B_OK;
+    failures += blake2b_hasher_update(hasher, (const uint8_t *)"c", 1) != BLAKE2B_OK;
+    failures += blake2b_hasher_finalize(hasher, out, sizeof(out)) != BLAKE2B_OK;
+    failures += check_output("test_hasher", 0, out, expected);
+
+    hasher = blake2b_hasher_new(32, NULL, 0);
+    failures += blake2b_hasher_finalize(hasher, out, sizeof(out)) != BLAKE2B_ERR_OUTPUT_LENGTH;
+
+    failures += blake2b_hasher_new(65, NULL, 0) != NULL;
+    blake2b_hasher_free(blake2b_hasher_new(64, NULL, 0));
+    blake2b_hasher_free(NULL);
+
+    return failures;
+}
+
+int main(void) {
+    int failures = test_decode_and_compress() + test_f() + test_hasher();
+
+    if (failures != 0) {
+        fprintf(stderr, "%d failures\n", failures);
+        return 1;
+    }
+
+    return 0;
+}
@@ -0,0 +1,84 @@
+//! Build the C test program in `tests/c` against the static library and run it.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Build the static library without the Python bindings, which the C ABI doesn't need, and return
+/// its path.  Cargo doesn't build the `staticlib` crate type for integration tests on its own.
+fn build_staticlib(target_dir: &Path) -> PathBuf {
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--no-default-features", "--target-dir"])
+        .arg(target_dir)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the static library");
+
+    target_dir.join("debug").join("libblake2b.a")
+}
+
+#[test]
+fn test_capi_eip_152() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    let staticlib = build_staticlib(&target_dir);
+
+    let program = target_dir.join("eip_152");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/eip_152.c"))
+        .arg(&staticlib)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile tests/c/eip_152.c");
+
+    let status = Command::new(&program).status().unwrap();
+    assert!(status.success(), "C ABI test program failed");
+}
+
+/// The committed header must agree with the constants in `src/capi.rs`, and negative values must
+/// be parenthesized so they expand safely.  `make check-header` diffs the whole file in CI.
+#[test]
+fn test_capi_header_constants() {
+    use blake2b::capi::*;
+
+    let header =
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include/blake2b.h"))
+            .unwrap();
+    let expected = [
+        ("BLAKE2B_OK", BLAKE2B_OK.to_string()),
+        (
+            "BLAKE2B_ERR_NULL_POINTER",
+            format!("({})", BLAKE2B_ERR_NULL_POINTER),
+        ),
+        (
+            "BLAKE2B_ERR_INPUT_LENGTH",
+            format!("({})", BLAKE2B_ERR_INPUT_LENGTH),
+        ),
+        (
+            "BLAKE2B_ERR_FINAL_BLOCK_FLAG",
+            format!("({})", BLAKE2B_ERR_FINAL_BLOCK_FLAG),
+        ),
+        (
+            "BLAKE2B_ERR_OUTPUT_LENGTH",
+            format!("({})", BLAKE2B_ERR_OUTPUT_LENGTH),
+        ),
+        ("BLAKE2B_F_INPUT_BYTES", BLAKE2B_F_INPUT_BYTES.to_string()),
+        ("BLAKE2B_F_OUTPUT_BYTES", BLAKE2B_F_OUTPUT_BYTES.to_string()),
+    ];
+    for (name, value) in expected.iter() {
+        let line = format!("#define {} {}", name, value);
+        assert!(
+            header.lines().any(|l| l == line),
+            "missing `{}` in include/blake2b.h",
+            line
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Compare F against the reference implementation in `tests/reference/mod.rs` on pseudo-random
+//! inputs.  The `differential_f` fuzz target does the same over arbitrary precompile input; this
+//! runs a fixed sample of it with every `cargo test`.
+
+mod reference;
+
+use blake2b::blake2b::F;
+
+/// SplitMix64, so the inputs are the same on every run without pulling in a rand crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, words: &mut [u64]) {
+        words.iter_mut().for_each(|word| *word = self.next());
+    }
+}
+
+#[test]
+fn test_f_matches_reference() {
+    let mut rng = SplitMix64(0);
+    // Every round count up to two full passes over SIGMA, then a few larger ones
+    let rounds = (0..=24).chain([100, 1000, 4095].iter().copied());
+
+    for rounds in rounds {
+        for _ in 0..8 {
+            let (mut h, mut m, mut t) = ([0u64; 8], [0u64; 16], [0u64; 2]);
+            rng.fill(&mut h);
+            rng.fill(&mut m);
+            rng.fill(&mut t);
+            let f = rng.next() & 1 == 1;
+
+            assert_eq!(
+                F(rounds, &h, &m, &t, f),
+                reference::F(rounds, &h, &m, &t, f),
+                "rounds = {}, h = {:x?}, m = {:x?}, t = {:x?}, f = {}",
+                rounds,
+                h,
+                m,
+                t,
+                f,
+            );
+        }
+    }
+}
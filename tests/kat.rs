@@ -0,0 +1,136 @@
+//! Run the known-answer tests from the BLAKE2 reference implementation in
+//! `tests/data/blake2-kat.json`.
+//!
+//! Each entry names a hash function and gives hex encoded input, key and output.  Entries for
+//! hash functions in `UNIMPLEMENTED` are skipped, so adding a mode takes a new arm in `digest`
+//! and removing it from that list.  The file is refreshed with `make update-kat`.
+
+// The vectors are read from disk and BLAKE2bp needs threads, neither of which wasm32 has.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use blake2b::hasher::{self, Params};
+use blake2b::parallel;
+
+/// Every hash function that appears in the upstream file.
+const MODES: &[&str] = &[
+    "blake2b", "blake2bp", "blake2xb", "blake2s", "blake2sp", "blake2xs",
+];
+
+/// The hash functions in `MODES` whose entries are skipped because this crate doesn't implement
+/// them.
+const UNIMPLEMENTED: &[&str] = &["blake2xb", "blake2s", "blake2sp", "blake2xs"];
+
+struct Kat {
+    hash: String,
+    input: Vec<u8>,
+    key: Vec<u8>,
+    output: Vec<u8>,
+}
+
+fn load_kats() -> Vec<Kat> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/blake2-kat.json");
+    let contents = fs::read_to_string(&path).unwrap();
+    let entries: Vec<Value> = serde_json::from_str(&contents).unwrap();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let field = |name: &str| entry[name].as_str().unwrap().to_string();
+            Kat {
+                hash: field("hash"),
+                input: hex::decode(field("in")).unwrap(),
+                key: hex::decode(field("key")).unwrap(),
+                output: hex::decode(field("out")).unwrap(),
+            }
+        })
+        .collect()
+}
+
+/// Calculate the digest of `kat` with its hash function, or return `None` if the hash function
+/// isn't implemented.
+fn digest(kat: &Kat) -> Option<Vec<u8>> {
+    let params = Params {
+        digest_length: kat.output.len(),
+        key: kat.key.clone(),
+        ..Params::default()
+    };
+
+    match &kat.hash[..] {
+        "blake2b" => Some(hasher::hash(&params, &kat.input).unwrap()),
+        "blake2bp" => Some(parallel::blake2bp(&params, &kat.input).unwrap()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_kat() {
+    let kats = load_kats();
+    let mut checked = 0;
+    let mut skipped = Vec::new();
+
+    for (i, kat) in kats.iter().enumerate() {
+        assert!(
+            MODES.contains(&&kat.hash[..]),
+            "unknown hash function {}",
+            kat.hash
+        );
+
+        match digest(kat) {
+            Some(result) => {
+                assert_eq!(
+                    hex::encode(result),
+                    hex::encode(&kat.output),
+                    "entry {} ({}, {} byte input, {} byte key)",
+                    i,
+                    kat.hash,
+                    kat.input.len(),
+                    kat.key.len(),
+                );
+                checked += 1;
+            }
+            None => {
+                if !skipped.contains(&&kat.hash[..]) {
+                    skipped.push(&kat.hash[..]);
+                }
+            }
+        }
+    }
+
+    for mode in MODES {
+        assert!(
+            kats.iter().any(|kat| kat.hash == *mode),
+            "no {} entries in the vector file",
+            mode
+        );
+    }
+
+    skipped.sort_unstable();
+    let mut unimplemented = UNIMPLEMENTED.to_vec();
+    unimplemented.sort_unstable();
+    assert_eq!(skipped, unimplemented, "skipped hash functions");
+
+    // The unkeyed and keyed blake2b and blake2bp entries
+    assert!(checked >= 1024, "only {} entries were checked", checked);
+}
+
+#[test]
+fn test_kat_streaming() {
+    for kat in load_kats().iter().filter(|kat| kat.hash == "blake2b") {
+        let params = Params {
+            digest_length: kat.output.len(),
+            key: kat.key.clone(),
+            ..Params::default()
+        };
+        let mut state = hasher::Blake2b::with_params(&params).unwrap();
+        for chunk in kat.input.chunks(7) {
+            state.update(chunk);
+        }
+
+        assert_eq!(state.finalize(), kat.output);
+    }
+}
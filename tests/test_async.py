@@ -0,0 +1,125 @@
+import asyncio
+import hashlib
+import pickle
+import threading
+
+import pytest
+
+import blake2b
+from blake2b import blake2b as _native
+
+EIP_152_VEC_5 = bytes.fromhex(
+    "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001"  # noqa: E501
+)
+EIP_152_VEC_5_OUTPUT = bytes.fromhex(
+    "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"  # noqa: E501
+)
+
+
+def test_decode_and_compress_async():
+    async def main():
+        return await asyncio.gather(*(
+            blake2b.decode_and_compress_async(EIP_152_VEC_5)
+            for _ in range(10)
+        ))
+
+    assert asyncio.run(main()) == [EIP_152_VEC_5_OUTPUT] * 10
+
+
+def test_compress_async():
+    args = blake2b.decode_parameters(EIP_152_VEC_5)
+
+    assert asyncio.run(blake2b.compress_async(*args)) == EIP_152_VEC_5_OUTPUT
+
+
+def test_async_does_not_block_event_loop():
+    # 2,000,000 rounds take long enough for the loop to keep ticking
+    slow_input = (2000000).to_bytes(4, 'big') + EIP_152_VEC_5[4:]
+
+    async def main():
+        ticks = 0
+        task = asyncio.ensure_future(
+            blake2b.decode_and_compress_async(slow_input),
+        )
+        while not task.done():
+            ticks += 1
+            await asyncio.sleep(0)
+        await task
+        return ticks
+
+    assert asyncio.run(main()) > 1
+
+
+def test_async_errors():
+    with pytest.raises(ValueError, match='213 bytes'):
+        asyncio.run(blake2b.decode_and_compress_async(EIP_152_VEC_5[:-1]))
+    with pytest.raises(ValueError, match='length 8'):
+        asyncio.run(blake2b.compress_async(12, [0] * 7, [0] * 16, [0] * 2, True))
+
+
+def test_crypto_generichash_update_async():
+    async def main():
+        state = blake2b.crypto_generichash_init(b'', 64)
+        update = blake2b.crypto_generichash_update_async(state, b'a' * 1000)
+        task = asyncio.ensure_future(update)
+        await asyncio.sleep(0)
+
+        if not task.done():
+            with pytest.raises(ValueError, match='being updated'):
+                blake2b.crypto_generichash_update(state, b'b')
+        await task
+
+        await blake2b.crypto_generichash_update_async(state, b'b')
+        return blake2b.crypto_generichash_final(state)
+
+    expected = hashlib.blake2b(b'a' * 1000 + b'b').digest()
+    assert asyncio.run(main()) == expected
+
+
+def test_setstate_while_updating():
+    workers = blake2b.get_async_workers()
+    blake2b.set_async_workers(1)
+    state = blake2b.crypto_generichash_init()
+    snapshot = pickle.dumps(state)
+    release, updated = threading.Event(), threading.Event()
+
+    # Keep the only worker busy so the update stays queued
+    _native.submit_decode_and_compress(EIP_152_VEC_5, lambda *_: release.wait())
+    _native.submit_generichash_update(state, b'a', lambda *_: updated.set())
+    with pytest.raises(ValueError, match='being updated'):
+        pickle.loads(snapshot).__setstate__(state.__getstate__())
+    with pytest.raises(ValueError, match='being updated'):
+        state.__setstate__(pickle.loads(snapshot).__getstate__())
+
+    release.set()
+    assert updated.wait(10)
+    blake2b.set_async_workers(workers)
+
+
+def test_async_queue_full():
+    workers = blake2b.get_async_workers()
+    blake2b.set_async_workers(1)
+    release = threading.Event()
+    _native.submit_decode_and_compress(EIP_152_VEC_5, lambda *_: release.wait())
+
+    # The busy worker leaves room for 64 queued calls
+    with pytest.raises(RuntimeError, match='too many jobs'):
+        for _ in range(66):
+            _native.submit_decode_and_compress(EIP_152_VEC_5, lambda *_: None)
+
+    release.set()
+    blake2b.set_async_workers(workers)
+
+
+def test_async_workers():
+    workers = blake2b.get_async_workers()
+
+    blake2b.set_async_workers(1)
+    assert blake2b.get_async_workers() == 1
+    assert asyncio.run(blake2b.decode_and_compress_async(EIP_152_VEC_5)) == (
+        EIP_152_VEC_5_OUTPUT
+    )
+
+    with pytest.raises(ValueError):
+        blake2b.set_async_workers(0)
+    blake2b.set_async_workers(workers)
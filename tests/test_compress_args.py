@@ -0,0 +1,80 @@
+import pytest
+
+import blake2b
+
+EIP_152_VEC_5 = bytes.fromhex(
+    "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001"  # noqa: E501
+)
+EIP_152_VEC_5_OUTPUT = bytes.fromhex(
+    "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"  # noqa: E501
+)
+
+
+def test_from_bytes():
+    args = blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+    rounds, h, m, t, f = blake2b.decode_parameters(EIP_152_VEC_5)
+
+    assert (args.rounds, args.h, args.m, args.t, args.f) == (rounds, h, m, t, f)
+    assert args.to_bytes() == EIP_152_VEC_5
+    assert args.compress() == EIP_152_VEC_5_OUTPUT
+
+
+def test_from_bytes_error():
+    with pytest.raises(ValueError):
+        blake2b.CompressArgs.from_bytes(EIP_152_VEC_5[:-1])
+
+
+def test_block():
+    args = blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+    block = EIP_152_VEC_5[68:196]
+
+    assert blake2b.CompressArgs.block(block, rounds=12, h=args.h, t=args.t, f=True) == args
+
+    default = blake2b.CompressArgs.block(block)
+    assert (default.rounds, default.t, default.f) == (12, [0, 0], False)
+    assert default.h == blake2b.CompressArgs.block(bytes(128)).h
+
+    with pytest.raises(ValueError):
+        blake2b.CompressArgs.block(block[:-1])
+
+    with pytest.raises(ValueError):
+        blake2b.CompressArgs.block(block, h=[0] * 7)
+
+
+def test_tuple_conversions():
+    as_tuple = blake2b.decode_parameters(EIP_152_VEC_5)
+    args = blake2b.CompressArgs.from_tuple(as_tuple)
+
+    assert args.to_tuple() == as_tuple
+    assert args == blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+    assert blake2b.compress(*args.to_tuple()) == EIP_152_VEC_5_OUTPUT
+
+    rounds, h, m, t, f = as_tuple
+    with pytest.raises(ValueError):
+        blake2b.CompressArgs.from_tuple((rounds, h, m[:-1], t, f))
+
+
+def test_eq():
+    args = blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+    other = blake2b.CompressArgs.from_bytes(b'\x00\x00\x00\x0b' + EIP_152_VEC_5[4:])
+
+    assert args == blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+    assert args != other
+    assert not args == other
+    assert args != EIP_152_VEC_5
+
+
+def test_repr():
+    args = blake2b.CompressArgs.block(bytes(128), rounds=1, h=[0] * 8)
+
+    assert repr(args) == (
+        'CompressArgs(rounds=1, h=[0, 0, 0, 0, 0, 0, 0, 0], '
+        'm=[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], t=[0, 0], f=False)'
+    )
+
+
+def test_compress_limits():
+    args = blake2b.CompressArgs.from_bytes(EIP_152_VEC_5)
+
+    with pytest.raises(blake2b.LimitExceeded):
+        args.compress(max_rounds=11)
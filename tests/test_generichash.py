@@ -0,0 +1,154 @@
+import hashlib
+import pickle
+
+from hypothesis import (
+    given,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+# Vectors from libsodium's test/default/generichash.exp and generichash3.exp
+# where the input is the bytes 0..i, the key is the bytes 0..i + 1 and the
+# output length is i + 1.
+KEYED_EXAMPLES = (
+    (15, "0c7c9c3922d41a7b2b3b20f92685d560"),
+    (16, "8508c01d19709bdd881866aa1f8c63ca06"),
+    (31, "a9f51bb7f6a3e9cdb96ce652c07d177962a348a9cced1b92f948187e59b44463"),
+    (63, "bd965bf31e87d70327536f2a341cebc4768eca275fa05ef98f7f1b71a0351298de006fba73fe6733ed01d75801b4a928e54231b38e38c562b2e33ea1284992fa"),  # noqa: E501
+)
+SALT_PERSONAL_EXAMPLES = (
+    (15, "5fbe885c4b2d4e0d78dc5905622a277a"),
+    (31, "9b273ebe335540b87be899abe169389ed61ed262c3a0a16e4998bbf752f0bee3"),
+    (63, "5fcdcc02be7714a0dbc77df498bf999ea9225d564adca1c121c9af03af92cac8177b9b4a86bcc47c79aa32aac58a3fef967b2132e9352d4613fe890beed2571b"),  # noqa: E501
+)
+SALT = b"5b6b41ed9b343fe0"
+PERSONAL = b"5126fb2a37400d2a"
+
+
+@pytest.mark.parametrize('i,expected', KEYED_EXAMPLES)
+def test_generichash_libsodium_vectors(i, expected):
+    message = bytes(range(i))
+    key = bytes(range(i + 1))
+
+    assert blake2b.crypto_generichash(message, i + 1, key).hex() == expected
+
+    state = blake2b.crypto_generichash_init(key, i + 1)
+    blake2b.crypto_generichash_update(state, message[:5])
+    blake2b.crypto_generichash_update(state, message[5:])
+    assert blake2b.crypto_generichash_final(state).hex() == expected
+
+
+@pytest.mark.parametrize('i,expected', SALT_PERSONAL_EXAMPLES)
+def test_generichash_salt_personal_libsodium_vectors(i, expected):
+    result = blake2b.crypto_generichash_blake2b_salt_personal(
+        bytes(range(i)),
+        i + 1,
+        bytes(range(i + 1)),
+        SALT,
+        PERSONAL,
+    )
+
+    assert result.hex() == expected
+
+
+@given(
+    st.binary(max_size=1000),
+    st.integers(min_value=16, max_value=64),
+    st.one_of(st.just(b''), st.binary(min_size=16, max_size=64)),
+)
+def test_generichash_equivalence_with_hashlib(message, outlen, key):
+    expected = hashlib.blake2b(message, digest_size=outlen, key=key).digest()
+
+    assert blake2b.crypto_generichash(message, outlen, key) == expected
+
+
+def test_generichash_state_finalized_twice():
+    state = blake2b.crypto_generichash_init()
+    blake2b.crypto_generichash_final(state)
+
+    with pytest.raises(ValueError):
+        blake2b.crypto_generichash_final(state)
+    with pytest.raises(ValueError):
+        blake2b.crypto_generichash_update(state, b'abc')
+
+
+@pytest.mark.parametrize('outlen,key', (
+    (15, b''),
+    (65, b''),
+    (32, b'\x00' * 15),
+    (32, b'\x00' * 65),
+))
+def test_generichash_bad_parameters(outlen, key):
+    with pytest.raises(ValueError):
+        blake2b.crypto_generichash(b'', outlen, key)
+
+
+@given(
+    st.binary(max_size=1000),
+    st.integers(min_value=0, max_value=1000),
+    st.integers(min_value=16, max_value=64),
+    st.one_of(st.just(b''), st.binary(min_size=16, max_size=64)),
+)
+def test_generichash_state_pickle(message, split, outlen, key):
+    expected = blake2b.crypto_generichash(message, outlen, key)
+
+    state = blake2b.crypto_generichash_init(key, outlen)
+    blake2b.crypto_generichash_update(state, message[:split])
+    restored = pickle.loads(pickle.dumps(state))
+
+    blake2b.crypto_generichash_update(restored, message[split:])
+    assert blake2b.crypto_generichash_final(restored) == expected
+
+
+def test_generichash_state_getstate_setstate():
+    state = blake2b.crypto_generichash_init(b'', 64)
+    blake2b.crypto_generichash_update(state, b'abc')
+    serialized = state.__getstate__()
+
+    assert serialized[:5] == b'B2BS\x01'
+    assert serialized[-4:] == b'\x03abc'
+
+    other = blake2b.crypto_generichash_init(b'', 32)
+    other.__setstate__(serialized)
+    assert blake2b.crypto_generichash_final(other) == hashlib.blake2b(b'abc').digest()
+
+    with pytest.raises(ValueError):
+        other.__setstate__(serialized[:-1])
+    with pytest.raises(ValueError):
+        other.__getstate__()
+
+
+@given(
+    st.binary(max_size=1000),
+    st.lists(st.binary(max_size=300), max_size=5),
+)
+def test_generichash_state_copy_shares_prefix(prefix, suffixes):
+    state = blake2b.crypto_generichash_init(b'', 64)
+    blake2b.crypto_generichash_update(state, prefix)
+
+    for suffix in suffixes:
+        copy = state.copy()
+        blake2b.crypto_generichash_update(copy, suffix)
+
+        expected = hashlib.blake2b(prefix + suffix).digest()
+        assert blake2b.crypto_generichash_final(copy) == expected
+
+
+def test_generichash_state_midstate():
+    message = bytes(range(200))
+    state = blake2b.crypto_generichash_init(b'', 64)
+    blake2b.crypto_generichash_update(state, message)
+    starting_state, offset_counters, buffered = state.midstate()
+
+    assert offset_counters == [128, 0]
+    assert buffered == message[128:]
+
+    # Finishing the message by hand with compress gives the same digest
+    block = buffered + b'\x00' * (128 - len(buffered))
+    words = [
+        int.from_bytes(block[i:i + 8], 'little') for i in range(0, 128, 8)
+    ]
+    result = blake2b.compress(12, starting_state, words, [200, 0], True)
+    assert result == hashlib.blake2b(message).digest()
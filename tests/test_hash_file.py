@@ -0,0 +1,75 @@
+import hashlib
+
+from hypothesis import (
+    given,
+    settings,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+MODES = ('blake2b', 'blake2bp', 'tree')
+
+
+@settings(max_examples=20)
+@given(
+    st.binary(max_size=5000),
+    st.integers(min_value=1, max_value=64),
+    st.binary(max_size=64),
+)
+def test_hash_file_matches_hashlib(tmp_path_factory, contents, digest_size, key):
+    path = tmp_path_factory.mktemp('hash_file') / 'contents'
+    path.write_bytes(contents)
+
+    expected = hashlib.blake2b(contents, digest_size=digest_size, key=key)
+    result = blake2b.hash_file(path, digest_size=digest_size, key=key)
+
+    assert result == expected.digest()
+
+
+@pytest.mark.parametrize('mode', MODES)
+def test_hash_file_modes(tmp_path, mode):
+    path = tmp_path / 'contents'
+    path.write_bytes(bytes(range(256)) * 10000)
+
+    result = blake2b.hash_file(str(path), mode=mode)
+
+    assert len(result) == 64
+    assert result == blake2b.hash_file(path, mode=mode)
+    if mode != 'blake2b':
+        assert result != blake2b.hash_file(path)
+
+
+def test_hash_file_blake2bp_reference_vector(tmp_path):
+    # The first unkeyed BLAKE2bp vector from the reference implementation
+    path = tmp_path / 'empty'
+    path.write_bytes(b'')
+
+    assert blake2b.hash_file(path, mode='blake2bp').hex() == (
+        'b5ef811a8038f70b628fa8b294daae7492b1ebe343a80eaabbf1f6ae664dd67b'
+        '9d90b0120791eab81dc96985f28849f6a305186a85501b405114bfa678df9380'
+    )
+
+
+def test_hash_file_blake2bp_short_digest(tmp_path):
+    # BLAKE2bp leaves hold the requested digest size in their parameter blocks,
+    # as in the reference implementation
+    path = tmp_path / 'contents'
+    path.write_bytes(bytes(range(256)) * 5)
+
+    assert blake2b.hash_file(path, mode='blake2bp', digest_size=32).hex() == (
+        'a70492195a6209753891748181e30669b23f0b1da357fe45ef51e9f84f78078b'
+    )
+
+
+def test_hash_file_errors(tmp_path):
+    path = tmp_path / 'contents'
+    path.write_bytes(b'abc')
+
+    with pytest.raises(ValueError):
+        blake2b.hash_file(path, mode='blake2s')
+    with pytest.raises(ValueError):
+        blake2b.hash_file(path, digest_size=65)
+    with pytest.raises(ValueError):
+        blake2b.hash_file(tmp_path / 'missing')
@@ -0,0 +1,202 @@
+import copy
+import hashlib
+import hmac
+import pickle
+import sys
+
+from hypothesis import (
+    given,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+
+@pytest.fixture
+def registry():
+    new = hashlib.new
+    available = set(hashlib.algorithms_available)
+    constructors = dict(blake2b._constructors)
+    added_names = dict(blake2b._added_names)
+    installed, hashlib_new = blake2b._installed, blake2b._hashlib_new
+    yield
+    hashlib.new = new
+    hashlib.algorithms_available.clear()
+    hashlib.algorithms_available.update(available)
+    blake2b._constructors.clear()
+    blake2b._constructors.update(constructors)
+    blake2b._added_names.clear()
+    blake2b._added_names.update(added_names)
+    blake2b._installed, blake2b._hashlib_new = installed, hashlib_new
+
+
+params = st.fixed_dictionaries({}, optional={
+    'digest_size': st.integers(min_value=1, max_value=64),
+    'key': st.binary(max_size=64),
+    'salt': st.binary(max_size=16),
+    'person': st.binary(max_size=16),
+    'fanout': st.integers(min_value=0, max_value=255),
+    'depth': st.integers(min_value=1, max_value=255),
+    'leaf_size': st.integers(min_value=0, max_value=2 ** 32 - 1),
+    'node_offset': st.integers(min_value=0, max_value=2 ** 64 - 1),
+    'node_depth': st.integers(min_value=0, max_value=255),
+    'inner_size': st.integers(min_value=0, max_value=64),
+    'last_node': st.booleans(),
+})
+
+
+@given(params, st.binary(max_size=1000))
+def test_equivalence_with_stdlib(kwargs, data):
+    expected = hashlib.blake2b(data, **kwargs)
+    actual = blake2b.Blake2b(data, **kwargs)
+
+    assert actual.digest() == expected.digest()
+    assert actual.hexdigest() == expected.hexdigest()
+    assert (actual.name, actual.digest_size, actual.block_size) == (
+        expected.name, expected.digest_size, expected.block_size,
+    )
+
+
+@given(st.lists(st.binary(max_size=300)))
+def test_incremental_equivalence_with_stdlib(chunks):
+    expected = hashlib.blake2b()
+    actual = blake2b.Blake2b()
+    for chunk in chunks:
+        expected.update(chunk)
+        actual.update(chunk)
+
+    assert actual.digest() == expected.digest()
+    assert actual.digest() == actual.digest()
+
+
+def test_large_input():
+    data = bytes(range(256)) * 4096
+
+    assert blake2b.Blake2b(data).digest() == hashlib.blake2b(data).digest()
+
+    state = blake2b.Blake2b(b'a')
+    state.update(data)
+    assert state.digest() == hashlib.blake2b(b'a' + data).digest()
+
+
+def test_copy():
+    state = blake2b.Blake2b(b'prefix', digest_size=32)
+    copy = state.copy()
+    copy.update(b'suffix')
+
+    assert state.digest() == hashlib.blake2b(b'prefix', digest_size=32).digest()
+    assert copy.digest() == hashlib.blake2b(b'prefixsuffix', digest_size=32).digest()
+
+
+@pytest.mark.parametrize('kwargs', (
+    {'digest_size': 0},
+    {'digest_size': 65},
+    {'key': bytes(65)},
+    {'salt': bytes(17)},
+    {'person': bytes(17)},
+    {'depth': 0},
+    {'inner_size': 65},
+))
+def test_invalid_parameters(kwargs):
+    with pytest.raises(ValueError):
+        blake2b.Blake2b(**kwargs)
+
+
+def test_class_constants():
+    for name in ('SALT_SIZE', 'PERSON_SIZE', 'MAX_KEY_SIZE', 'MAX_DIGEST_SIZE'):
+        assert getattr(blake2b.Blake2b, name) == getattr(hashlib.blake2b, name)
+
+
+@given(params, st.binary(max_size=300), st.binary(max_size=300))
+def test_pickle(kwargs, data, more):
+    original = blake2b.Blake2b(data, **kwargs)
+
+    for restored in (pickle.loads(pickle.dumps(original)), copy.deepcopy(original)):
+        assert restored.digest() == original.digest()
+        restored.update(more)
+        original_copy = original.copy()
+        original_copy.update(more)
+        assert restored.digest() == original_copy.digest()
+
+
+def test_setstate_errors():
+    with pytest.raises(ValueError):
+        blake2b.Blake2b().__setstate__(b'not a hashing state')
+
+
+@given(st.binary(max_size=300), st.binary(max_size=1000))
+def test_hmac_digestmod(key, msg):
+    expected = hmac.new(key, msg, hashlib.blake2b)
+    actual = hmac.new(key, msg, blake2b.Blake2b)
+
+    assert actual.digest() == expected.digest()
+    assert actual.copy().hexdigest() == expected.hexdigest()
+    assert hmac.digest(key, msg, blake2b.Blake2b) == expected.digest()
+
+
+def test_register(registry):
+    blake2b.register()
+
+    assert 'blake2b-rs' in hashlib.algorithms_available
+    assert isinstance(hashlib.new('blake2b-rs'), blake2b.Blake2b)
+    assert hashlib.new('BLAKE2B-RS', b'abc').digest() == hashlib.blake2b(b'abc').digest()
+    assert hashlib.new('blake2b-rs', b'abc', digest_size=32).digest() == (
+        hashlib.blake2b(b'abc', digest_size=32).digest()
+    )
+    assert hashlib.new('sha256', b'abc').digest() == hashlib.sha256(b'abc').digest()
+
+
+@pytest.mark.skipif(
+    sys.version_info[:2] == (3, 10),
+    reason="hmac on Python 3.10 doesn't fall back to hashlib.new for names OpenSSL 3 rejects",
+)
+def test_register_hmac_digestmod(registry):
+    blake2b.register()
+
+    key, msg = b'key', b'message'
+    assert hmac.new(key, msg, 'blake2b-rs').digest() == (
+        hmac.new(key, msg, hashlib.blake2b).digest()
+    )
+
+
+def test_register_replaces_stdlib_name(registry):
+    blake2b.register('blake2b')
+
+    assert isinstance(hashlib.new('blake2b'), blake2b.Blake2b)
+    assert isinstance(hashlib.new('blake2s'), type(hashlib.blake2s()))
+
+    # The standard library name stays available once unregistered
+    blake2b.unregister('blake2b')
+    assert 'blake2b' in hashlib.algorithms_available
+    assert not isinstance(hashlib.new('blake2b'), blake2b.Blake2b)
+
+
+def test_register_is_idempotent_and_reversible(registry):
+    new, available = hashlib.new, set(hashlib.algorithms_available)
+
+    blake2b.register()
+    blake2b.register()
+    blake2b.register('other-name')
+    blake2b.unregister()
+    assert 'blake2b-rs' not in hashlib.algorithms_available
+    assert isinstance(hashlib.new('other-name'), blake2b.Blake2b)
+    with pytest.raises(ValueError):
+        hashlib.new('blake2b-rs')
+
+    blake2b.unregister('other-name')
+    blake2b.unregister('other-name')
+    assert hashlib.new is new
+    assert hashlib.algorithms_available == available
+
+
+def test_new():
+    new = hashlib.new
+
+    assert isinstance(blake2b.new('blake2b'), blake2b.Blake2b)
+    assert blake2b.new('BLAKE2B-RS', b'abc', digest_size=32).digest() == (
+        hashlib.blake2b(b'abc', digest_size=32).digest()
+    )
+    assert blake2b.new('sha256', b'abc').digest() == hashlib.sha256(b'abc').digest()
+    assert hashlib.new is new
+    assert 'blake2b-rs' not in hashlib.algorithms_available
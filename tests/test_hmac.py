@@ -0,0 +1,48 @@
+import hashlib
+import hmac
+
+from hypothesis import (
+    given,
+    strategies as st,
+)
+
+import blake2b
+
+
+@given(st.binary(max_size=300), st.binary(max_size=1000))
+def test_hmac_equivalence_with_stdlib(key, msg):
+    expected = hmac.new(key, msg, hashlib.blake2b).digest()
+
+    assert blake2b.hmac_blake2b(key, msg) == expected
+    assert blake2b.HMAC(key, msg).digest() == expected
+
+
+@given(st.binary(max_size=300), st.lists(st.binary(max_size=200)))
+def test_hmac_incremental_equivalence_with_stdlib(key, chunks):
+    expected = hmac.new(key, digestmod=hashlib.blake2b)
+    actual = blake2b.HMAC(key)
+    for chunk in chunks:
+        expected.update(chunk)
+        actual.update(chunk)
+
+    assert actual.digest() == expected.digest()
+    assert actual.hexdigest() == expected.hexdigest()
+    assert actual.copy().digest() == expected.copy().digest()
+
+
+def test_hmac_attributes():
+    stdlib = hmac.new(b'key', digestmod=hashlib.blake2b)
+    actual = blake2b.HMAC(b'key')
+
+    assert actual.name == stdlib.name
+    assert actual.digest_size == stdlib.digest_size
+    assert actual.block_size == stdlib.block_size
+
+
+def test_hmac_verify():
+    state = blake2b.HMAC(b'key', b'message')
+    tag = state.digest()
+
+    assert state.verify(tag)
+    assert not state.verify(tag[:32])
+    assert not state.verify(bytes([tag[0] ^ 1]) + tag[1:])
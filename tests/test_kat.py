@@ -0,0 +1,28 @@
+import json
+import os
+
+import pytest
+
+import blake2b
+
+KAT_PATH = os.path.join(os.path.dirname(__file__), 'data', 'blake2-kat.json')
+
+with open(KAT_PATH) as f:
+    KATS = json.load(f)
+
+BLAKE2B_KATS = [kat for kat in KATS if kat['hash'] == 'blake2b']
+
+
+def test_kat_file_has_blake2b_vectors():
+    # The unkeyed and keyed blake2b entries
+    assert len(BLAKE2B_KATS) >= 512
+
+
+@pytest.mark.parametrize('kat', BLAKE2B_KATS)
+def test_blake2b_kat(kat):
+    message = bytes.fromhex(kat['in'])
+    key = bytes.fromhex(kat['key'])
+    expected = kat['out']
+
+    result = blake2b.crypto_generichash(message, len(expected) // 2, key)
+    assert result.hex() == expected
@@ -0,0 +1,112 @@
+import hashlib
+import hmac
+
+from hypothesis import (
+    given,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+# The inputs of the test cases from RFC 5869 appendix A with the expected
+# outputs for the native keyed mode KDF and for HKDF-BLAKE2b-512.
+RFC_5869_INPUTS = (
+    (bytes([0x0b] * 22), bytes(range(0x0d)), bytes(range(0xf0, 0xfa)), 42),
+    (bytes(range(0x50)), bytes(range(0x60, 0xb0)), bytes(range(0xb0, 0x100)), 82),  # noqa: E501
+    (bytes([0x0b] * 22), b'', b'', 42),
+)
+KEYED_OKMS = (
+    "18eca4e83bda55e1a926e5dfd8869bb9f02f5de7772796f7e38350921f6cff0b7d160f6812cb1fde8a64",  # noqa: E501
+    "4746dc89f46071a8e42b21fe738100a586ea6e2f8f4edafcc39bf5d5f0483f04ac047a9c422b22b26a1a895ad7a20d0d9c2d6b45343afa42f33b22f136f9d79790128eea10ec3cd493e4afcfa8f4efa59186",  # noqa: E501
+    "0aad709babc52bc04596ee1d80741a999a0828c95c38faeda79c984961d6b5ec50805e48bc7065d24f2f",  # noqa: E501
+)
+HKDF_OKMS = (
+    "8815e1a85b5e90e6174323fdd180248887a7138af6dc5c8320fde21a60a078808267d6a41b6a938d7b30",  # noqa: E501
+    "bb19eccde3ff3f41a8b3a147eb4fa640599a4e2194aadd98c0458f7ec05ac995238f9b6002d5bcbfa7c67975965de73ff014e55acff823162d6d5c04498501b1acb5e99058466d1a440ffc544f408e89f555",  # noqa: E501
+    "817520332f597bd8f557a4b40fddfe7674f1edac6c8a1a36fa0546b649bfae4a2ed3f34d03fdef572d51",  # noqa: E501
+)
+
+
+def reference_hkdf(ikm, salt, info, length):
+    prk = hmac.new(salt or b'\x00' * 64, ikm, hashlib.blake2b).digest()
+
+    okm = b''
+    t = b''
+    counter = 1
+    while len(okm) < length:
+        t = hmac.new(prk, t + info + bytes([counter]), hashlib.blake2b).digest()
+        okm += t
+        counter += 1
+
+    return okm[:length]
+
+
+@pytest.mark.parametrize('inputs,expected', zip(RFC_5869_INPUTS, KEYED_OKMS))
+def test_kdf_vectors(inputs, expected):
+    ikm, salt, info, length = inputs
+
+    assert blake2b.kdf(ikm, salt, info, length).hex() == expected
+
+    prk = blake2b.kdf_extract(salt, ikm)
+    assert blake2b.kdf_expand(prk, info, length).hex() == expected
+
+
+@pytest.mark.parametrize('inputs,expected', zip(RFC_5869_INPUTS, HKDF_OKMS))
+def test_hkdf_vectors(inputs, expected):
+    ikm, salt, info, length = inputs
+
+    assert blake2b.hkdf(ikm, salt, info, length).hex() == expected
+
+    prk = blake2b.hkdf_extract(salt, ikm)
+    assert blake2b.hkdf_expand(prk, info, length).hex() == expected
+
+
+@given(
+    st.binary(max_size=200),
+    st.binary(max_size=200),
+    st.binary(max_size=200),
+    st.integers(min_value=0, max_value=1000),
+)
+def test_hkdf_equivalence_with_stdlib_hmac(ikm, salt, info, length):
+    expected = reference_hkdf(ikm, salt, info, length)
+
+    assert blake2b.hkdf(ikm, salt, info, length) == expected
+
+
+def test_kdf_output_too_long():
+    with pytest.raises(ValueError):
+        blake2b.kdf(b'ikm', length=255 * 64 + 1)
+    with pytest.raises(ValueError):
+        blake2b.hkdf(b'ikm', length=255 * 64 + 1)
+
+
+@given(
+    st.binary(max_size=300),
+    st.binary(max_size=200),
+    st.integers(min_value=1, max_value=20),
+    st.integers(min_value=1, max_value=300),
+)
+def test_pbkdf2_equivalence_with_stdlib(password, salt, iterations, dklen):
+    expected = hashlib.pbkdf2_hmac('blake2b512', password, salt, iterations, dklen)
+
+    assert blake2b.pbkdf2_hmac_blake2b(password, salt, iterations, dklen) == expected
+
+
+def test_pbkdf2_vectors():
+    # RFC 6070 inputs
+    assert blake2b.pbkdf2_hmac_blake2b(b'password', b'salt', 4096).hex() == (
+        "9d4f324ef40b5be658fa0ab94a168664f060c0c9cc85a02ac83f2d44088cb7e7b812ef60e9b1673d4fd77240a68607d72b912e18a0ea4772f476be7583b66970"  # noqa: E501
+    )
+    assert blake2b.pbkdf2_hmac_blake2b(b'pass\0word', b'sa\0lt', 4096, 16).hex() == (
+        "f6c776d6265b53e32fa746713464fe59"
+    )
+
+
+def test_pbkdf2_errors():
+    with pytest.raises(ValueError):
+        blake2b.pbkdf2_hmac_blake2b(b'password', b'salt', 0)
+    with pytest.raises(ValueError):
+        blake2b.pbkdf2_hmac_blake2b(b'password', b'salt', 1, 0)
+    with pytest.raises(OverflowError):
+        blake2b.pbkdf2_hmac_blake2b(b'password', b'salt', -1)
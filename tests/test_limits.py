@@ -0,0 +1,101 @@
+import asyncio
+import time
+
+import pytest
+
+import blake2b
+
+EIP_152_VEC_5 = bytes.fromhex(
+    "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001"  # noqa: E501
+)
+EIP_152_VEC_5_OUTPUT = bytes.fromhex(
+    "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"  # noqa: E501
+)
+MAX_ROUNDS_INPUT = b'\xff\xff\xff\xff' + EIP_152_VEC_5[4:]
+
+
+@pytest.fixture
+def default_limits():
+    yield
+    blake2b.set_limits()
+
+
+def test_limit_exceeded_is_value_error():
+    assert issubclass(blake2b.LimitExceeded, ValueError)
+
+
+def test_max_rounds():
+    assert blake2b.decode_and_compress(EIP_152_VEC_5, max_rounds=12) == EIP_152_VEC_5_OUTPUT
+
+    with pytest.raises(blake2b.LimitExceeded):
+        blake2b.decode_and_compress(EIP_152_VEC_5, max_rounds=11)
+
+    args = blake2b.decode_parameters(EIP_152_VEC_5)
+    with pytest.raises(blake2b.LimitExceeded):
+        blake2b.compress(*args, max_rounds=11)
+
+
+def test_time_budget_rejects_before_hashing():
+    start = time.monotonic()
+    with pytest.raises(blake2b.LimitExceeded):
+        blake2b.decode_and_compress(MAX_ROUNDS_INPUT, time_budget=0.1)
+
+    assert time.monotonic() - start < 5
+
+    assert blake2b.decode_and_compress(EIP_152_VEC_5, time_budget=1.0) == EIP_152_VEC_5_OUTPUT
+
+
+def test_negative_time_budget():
+    with pytest.raises(ValueError):
+        blake2b.decode_and_compress(EIP_152_VEC_5, time_budget=-1.0)
+
+    with pytest.raises(ValueError):
+        blake2b.set_limits(time_budget=-1.0)
+
+
+def test_default_limits(default_limits):
+    assert blake2b.get_limits() == (None, None)
+
+    blake2b.set_limits(max_rounds=11, time_budget=2.5)
+    assert blake2b.get_limits() == (11, 2.5)
+
+    with pytest.raises(blake2b.LimitExceeded):
+        blake2b.decode_and_compress(EIP_152_VEC_5)
+
+    # call arguments override the process-wide default
+    assert blake2b.decode_and_compress(EIP_152_VEC_5, max_rounds=12) == EIP_152_VEC_5_OUTPUT
+
+    blake2b.set_limits()
+    assert blake2b.get_limits() == (None, None)
+    assert blake2b.decode_and_compress(EIP_152_VEC_5) == EIP_152_VEC_5_OUTPUT
+
+
+def test_default_limits_async(default_limits):
+    blake2b.set_limits(max_rounds=11)
+
+    with pytest.raises(blake2b.LimitExceeded):
+        asyncio.run(blake2b.decode_and_compress_async(EIP_152_VEC_5))
+
+    args = blake2b.decode_parameters(EIP_152_VEC_5)
+    with pytest.raises(blake2b.LimitExceeded):
+        asyncio.run(blake2b.compress_async(*args))
+
+
+def test_limits_async_arguments(default_limits):
+    args = blake2b.decode_parameters(EIP_152_VEC_5)
+
+    with pytest.raises(blake2b.LimitExceeded):
+        asyncio.run(blake2b.decode_and_compress_async(EIP_152_VEC_5, max_rounds=11))
+    with pytest.raises(blake2b.LimitExceeded):
+        asyncio.run(blake2b.compress_async(*args, max_rounds=11))
+    with pytest.raises(blake2b.LimitExceeded):
+        asyncio.run(blake2b.decode_and_compress_async(MAX_ROUNDS_INPUT, time_budget=0.1))
+
+    # call arguments override the process-wide default
+    blake2b.set_limits(max_rounds=11)
+    assert asyncio.run(
+        blake2b.decode_and_compress_async(EIP_152_VEC_5, max_rounds=12, time_budget=1.0)
+    ) == EIP_152_VEC_5_OUTPUT
+    assert asyncio.run(
+        blake2b.compress_async(*args, max_rounds=12, time_budget=1.0)
+    ) == EIP_152_VEC_5_OUTPUT
@@ -0,0 +1,118 @@
+from hypothesis import (
+    given,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+u64 = st.integers(min_value=0, max_value=2 ** 64 - 1)
+
+rounds = st.integers(min_value=0, max_value=24)
+starting_states = st.lists(u64, min_size=8, max_size=8)
+blocks = st.lists(u64, min_size=16, max_size=16)
+offset_counters = st.lists(u64, min_size=2, max_size=2)
+final_block_flags = st.booleans()
+states = st.lists(u64, min_size=16, max_size=16)
+
+IV = blake2b.CompressArgs.block(bytes(128)).h
+MASK = 2 ** 64 - 1
+SIGMA = (
+    (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
+    (14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3),
+    (11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4),
+    (7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8),
+    (9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13),
+    (2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9),
+    (12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11),
+    (13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10),
+    (6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5),
+    (10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0),
+)
+
+
+def init_v(starting_state, offset_counters, final_block_flag):
+    v = list(starting_state) + list(IV)
+    v[12] ^= offset_counters[0]
+    v[13] ^= offset_counters[1]
+    if final_block_flag:
+        v[14] ^= MASK
+    return v
+
+
+def output_from_v(starting_state, v):
+    words = [starting_state[i] ^ v[i] ^ v[i + 8] for i in range(8)]
+    return b''.join(word.to_bytes(8, 'little') for word in words)
+
+
+@given(
+    rounds,
+    starting_states,
+    blocks,
+    offset_counters,
+    final_block_flags,
+)
+def test_round_matches_compress(rounds, starting_state, block, offset_counters, final_block_flag):
+    v = init_v(starting_state, offset_counters, final_block_flag)
+    for r in range(rounds):
+        v = blake2b.round(v, block, r)
+
+    assert output_from_v(starting_state, v) == blake2b.compress(
+        rounds, starting_state, block, offset_counters, final_block_flag,
+    )
+
+
+@given(states, blocks, st.integers(min_value=0, max_value=100))
+def test_round_is_g_on_columns_then_diagonals(v, block, r):
+    s = SIGMA[r % 10]
+    expected = v
+    for i, (a, b, c, d) in enumerate((
+        (0, 4, 8, 12), (1, 5, 9, 13), (2, 6, 10, 14), (3, 7, 11, 15),
+        (0, 5, 10, 15), (1, 6, 11, 12), (2, 7, 8, 13), (3, 4, 9, 14),
+    )):
+        expected = blake2b.G(expected, a, b, c, d, block[s[2 * i]], block[s[2 * i + 1]])
+
+    assert blake2b.round(v, block, r) == expected
+
+
+@given(rounds, starting_states, offset_counters, final_block_flags)
+def test_permute_matches_compress(rounds, starting_state, offset_counters, final_block_flag):
+    v = blake2b.permute(init_v(starting_state, offset_counters, final_block_flag), rounds)
+
+    assert output_from_v(starting_state, v) == blake2b.compress(
+        rounds, starting_state, [0] * 16, offset_counters, final_block_flag,
+    )
+
+
+@given(states)
+def test_permute_is_rounds_with_zero_message(v):
+    expected = v
+    for r in range(12):
+        expected = blake2b.round(expected, [0] * 16, r)
+
+    assert blake2b.permute(v) == expected
+    assert blake2b.permute(v, 0) == v
+
+
+def test_g_errors():
+    with pytest.raises(ValueError):
+        blake2b.G([0] * 15, 0, 4, 8, 12, 0, 0)
+
+    with pytest.raises(ValueError, match='less than 16'):
+        blake2b.G([0] * 16, 0, 4, 8, 16, 0, 0)
+
+
+def test_round_errors():
+    with pytest.raises(ValueError):
+        blake2b.round([0] * 15, [0] * 16, 0)
+
+    with pytest.raises(ValueError):
+        blake2b.round([0] * 16, [0] * 17, 0)
+
+
+def test_permute_limits():
+    with pytest.raises(ValueError):
+        blake2b.permute([0] * 8)
+
+    with pytest.raises(blake2b.LimitExceeded):
+        blake2b.permute([0] * 16, 13, max_rounds=12)
@@ -0,0 +1,163 @@
+"""
+Checks that the type stubs in ``blake2b/blake2b.pyi`` describe the compiled
+extension module: the same names, the same parameters with the same defaults,
+and the same methods and properties on each class.
+"""
+import ast
+import builtins
+import inspect
+import pathlib
+import sys
+
+import pytest
+
+import blake2b
+from blake2b import blake2b as compiled
+
+STUB_PATH = pathlib.Path(blake2b.__file__).parent / 'blake2b.pyi'
+
+# Special methods the stubs describe, which are checked along with the public
+# methods
+SPECIAL_METHODS = {
+    '__eq__',
+    '__getstate__',
+    '__iter__',
+    '__next__',
+    '__reduce__',
+    '__setstate__',
+}
+
+
+def load_stubs():
+    tree = ast.parse(STUB_PATH.read_text())
+    functions = {}
+    classes = {}
+
+    for node in tree.body:
+        if isinstance(node, ast.FunctionDef):
+            functions[node.name] = node
+        elif isinstance(node, ast.ClassDef):
+            classes[node.name] = node
+
+    return functions, classes
+
+
+STUB_FUNCTIONS, STUB_CLASSES = load_stubs()
+
+
+def is_decorated(node, name):
+    return any(
+        isinstance(decorator, ast.Name) and decorator.id == name
+        for decorator in node.decorator_list
+    )
+
+
+def stub_parameters(node, skip_self):
+    """
+    The ``(name, default)`` pairs of the parameters of a stub function, with
+    ``inspect.Parameter.empty`` for parameters without a default.
+    """
+    args = node.args.args
+    if skip_self:
+        args = args[1:]
+    defaults = [inspect.Parameter.empty] * (len(args) - len(node.args.defaults))
+    defaults += [ast.literal_eval(default) for default in node.args.defaults]
+
+    args += node.args.kwonlyargs
+    defaults += [
+        inspect.Parameter.empty if default is None else ast.literal_eval(default)
+        for default in node.args.kw_defaults
+    ]
+
+    return [(arg.arg, default) for arg, default in zip(args, defaults)]
+
+
+def compiled_parameters(obj):
+    parameters = inspect.signature(obj).parameters.values()
+    return [
+        (parameter.name, parameter.default)
+        for parameter in parameters
+        if parameter.name != 'self' and parameter.kind != parameter.POSITIONAL_ONLY
+    ]
+
+
+def public_names(obj):
+    return {name for name in vars(obj) if not name.startswith('_')}
+
+
+def test_stubs_cover_module():
+    stub_names = set(STUB_FUNCTIONS) | set(STUB_CLASSES)
+
+    assert stub_names == public_names(compiled)
+
+
+def test_stubs_cover_package():
+    python_names = {'compress_async', 'crypto_generichash_update_async',
+                    'decode_and_compress_async', 'new', 'register', 'test',
+                    'unregister'}
+
+    assert set(blake2b.__all__) - python_names <= public_names(compiled)
+
+
+def test_star_import_keeps_builtins():
+    assert not set(blake2b.__all__) & set(dir(builtins))
+
+
+def test_round_is_exported_outside_all():
+    assert blake2b.round is compiled.round
+    assert 'round' not in blake2b.__all__
+
+
+@pytest.mark.parametrize('name', sorted(STUB_FUNCTIONS))
+def test_function_signatures(name):
+    node = STUB_FUNCTIONS[name]
+
+    assert stub_parameters(node, False) == compiled_parameters(getattr(compiled, name))
+
+
+@pytest.mark.parametrize('name', sorted(STUB_CLASSES))
+def test_class_members(name):
+    node = STUB_CLASSES[name]
+    cls = getattr(compiled, name)
+
+    stub_methods = {
+        child.name: child
+        for child in node.body
+        if isinstance(child, ast.FunctionDef)
+    }
+    properties = {
+        method for method, child in stub_methods.items()
+        if is_decorated(child, 'property')
+    }
+    attributes = {
+        child.target.id: child.annotation.id
+        for child in node.body
+        if isinstance(child, ast.AnnAssign)
+    }
+    compiled_members = public_names(cls) | (SPECIAL_METHODS & set(vars(cls)))
+
+    assert set(stub_methods) - {'__init__'} | set(attributes) == compiled_members
+    for attribute, annotation in attributes.items():
+        assert type(getattr(cls, attribute)).__name__ == annotation, attribute
+
+    for method, child in stub_methods.items():
+        if method in properties:
+            assert inspect.isgetsetdescriptor(vars(cls)[method]), method
+        elif method == '__init__':
+            # The limited API only gives classes a text signature on Python 3.10 and later
+            if sys.version_info >= (3, 10):
+                assert stub_parameters(child, True) == compiled_parameters(cls)
+        elif not method.startswith('__'):
+            static = is_decorated(child, 'staticmethod')
+            assert stub_parameters(child, not static) == compiled_parameters(
+                getattr(cls, method),
+            ), method
+
+
+def test_exception_classes():
+    assert STUB_CLASSES['LimitExceeded'].bases[0].id == 'ValueError'
+    assert issubclass(compiled.LimitExceeded, ValueError)
+
+
+def test_py_typed():
+    assert (STUB_PATH.parent / 'py.typed').exists()
@@ -0,0 +1,73 @@
+from hypothesis import (
+    given,
+    strategies as st,
+)
+import pytest
+
+import blake2b
+
+u64 = st.integers(min_value=0, max_value=2 ** 64 - 1)
+
+rounds = st.integers(min_value=0, max_value=24)
+starting_states = st.lists(u64, min_size=8, max_size=8)
+blocks = st.lists(u64, min_size=16, max_size=16)
+offset_counters = st.lists(u64, min_size=2, max_size=2)
+final_block_flags = st.booleans()
+
+EIP_152_VEC_5 = bytes.fromhex(
+    "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001"  # noqa: E501
+)
+
+
+def output_from_v(starting_state, v):
+    words = [starting_state[i] ^ v[i] ^ v[i + 8] for i in range(8)]
+    return b''.join(word.to_bytes(8, 'little') for word in words)
+
+
+@given(
+    rounds,
+    starting_states,
+    blocks,
+    offset_counters,
+    final_block_flags,
+)
+def test_trace_matches_compress(
+        rounds,
+        starting_state,
+        block,
+        offset_counter,
+        final_block_flag,
+):
+    args = (rounds, starting_state, block, offset_counter, final_block_flag)
+    expected = blake2b.compress(*args)
+
+    g_steps = list(blake2b.compress_trace(*args))
+    round_steps = list(blake2b.compress_trace(*args, per_round=True))
+
+    assert len(g_steps) == rounds * 8
+    assert len(round_steps) == rounds
+    assert g_steps[7::8] == [
+        (r, 7, sigma, v) for (r, _, sigma, v) in round_steps
+    ]
+    if rounds > 0:
+        assert output_from_v(starting_state, round_steps[-1][3]) == expected
+
+
+def test_trace_steps():
+    args = blake2b.decode_parameters(EIP_152_VEC_5)
+    trace = blake2b.compress_trace(*args)
+
+    assert iter(trace) is trace
+    round_number, g, sigma, v = next(trace)
+    assert (round_number, g) == (0, 0)
+    assert sigma == list(range(16))
+    assert len(v) == 16
+
+    for _ in trace:
+        pass
+    assert trace.output() == blake2b.decode_and_compress(EIP_152_VEC_5)
+
+
+def test_trace_errors():
+    with pytest.raises(ValueError):
+        blake2b.compress_trace(12, [0] * 7, [0] * 16, [0] * 2, True)
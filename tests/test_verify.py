@@ -0,0 +1,37 @@
+import hashlib
+import hmac
+
+from hypothesis import (
+    given,
+    strategies as st,
+)
+
+import blake2b
+
+
+@given(st.binary(max_size=64), st.binary(max_size=64))
+def test_compare_digest_equivalence_with_stdlib(a, b):
+    assert blake2b.compare_digest(a, b) == hmac.compare_digest(a, b)
+    assert blake2b.compare_digest(a, a)
+
+
+@given(
+    st.binary(max_size=64),
+    st.binary(max_size=1000),
+    st.integers(min_value=1, max_value=64),
+)
+def test_verify_keyed_tag(key, message, digest_size):
+    tag = hashlib.blake2b(message, digest_size=digest_size, key=key).digest()
+
+    assert blake2b.verify(tag, key, message, digest_size)
+    assert not blake2b.verify(bytes([tag[0] ^ 1]) + tag[1:], key, message, digest_size)
+    assert not blake2b.verify(tag, key, message + b'\x00', digest_size)
+
+
+@given(st.binary(max_size=64), st.binary(max_size=1000))
+def test_verify_rejects_truncated_tag(key, message):
+    tag = hashlib.blake2b(message, key=key).digest()
+
+    assert blake2b.verify(tag, key, message)
+    for length in (0, 1, 16, 63):
+        assert not blake2b.verify(tag[:length], key, message)
@@ -0,0 +1,92 @@
+//! Run the EIP-152 test vectors against the JavaScript API under a wasm runtime.
+//!
+//! Run with `make test_wasm`, which needs `wasm-bindgen-test-runner` from the `wasm-bindgen-cli`
+//! crate and node.js.
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+use blake2b::wasm::{compress, decode_and_compress, Hasher};
+
+/// EIP-152 test vectors 4 to 7
+const SUCCESS_EXAMPLES: &[(&str, &str)] = &[
+    (
+        "0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b",
+    ),
+    (
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    ),
+    (
+        "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000",
+        "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d2875298743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735",
+    ),
+    (
+        "0000000148c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+        "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fba551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421",
+    ),
+];
+
+/// EIP-152 test vectors 0 to 3
+const ERROR_EXAMPLES: &[&str] = &[
+    "",
+    "00000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    "000000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000001",
+    "0000000c48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b61626300000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000002",
+];
+
+#[wasm_bindgen_test]
+fn test_decode_and_compress() {
+    for (input, expected) in SUCCESS_EXAMPLES {
+        let result = decode_and_compress(&hex::decode(input).unwrap()).unwrap();
+
+        assert_eq!(hex::encode(result), *expected);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_decode_and_compress_error() {
+    for input in ERROR_EXAMPLES {
+        assert!(decode_and_compress(&hex::decode(input).unwrap()).is_err());
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_compress() {
+    for (input, expected) in SUCCESS_EXAMPLES {
+        let input = hex::decode(input).unwrap();
+        let rounds = u32::from_be_bytes([input[0], input[1], input[2], input[3]]);
+
+        let result = compress(
+            rounds,
+            &input[4..68],
+            &input[68..196],
+            &input[196..212],
+            input[212] == 1,
+        )
+        .unwrap();
+
+        assert_eq!(hex::encode(result), *expected);
+    }
+
+    assert!(compress(12, &[0; 63], &[0; 128], &[0; 16], true).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_hasher() {
+    // Twelve rounds of F over the padded block "abc" in EIP-152 test vector 5 is the final
+    // compression of blake2b-512("abc")
+    let expected = SUCCESS_EXAMPLES[1].1;
+
+    let mut hasher = Hasher::new(None, None).unwrap();
+    hasher.update(b"ab");
+    let copy = hasher.copy();
+    hasher.update(b"c");
+
+    assert_eq!(hex::encode(hasher.digest()), expected);
+    assert_ne!(copy.digest(), hasher.digest());
+    assert_eq!(Hasher::new(Some(32), None).unwrap().digest().len(), 32);
+    assert!(Hasher::new(Some(65), None).is_err());
+}